@@ -0,0 +1,125 @@
+use std::collections::HashSet;
+use std::sync::atomic::AtomicBool;
+
+use serde::Serialize;
+
+use crate::ai::chat::{stream_chat, ChatMessage};
+use crate::ai::embedding::EmbeddingConfig;
+use crate::models::{ConversationMessage, GraphData};
+
+/// Long fields are cut so a wide neighborhood still fits the prompt.
+const MAX_FIELD_CHARS: usize = 600;
+
+/// Earlier turns sent along for follow-up questions.
+const MAX_HISTORY_MESSAGES: usize = 10;
+
+#[derive(Debug, Clone)]
+pub struct Answer {
+    pub answer: String,
+    pub cited_ids: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct PromptContext<'a> {
+    question: &'a str,
+    mashes: Vec<PromptMash<'a>>,
+    edges: Vec<PromptEdge<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PromptMash<'a> {
+    id: &'a str,
+    #[serde(rename = "type")]
+    mash_type: &'a str,
+    summary: &'a str,
+    context: String,
+    memo: String,
+    created_at: i64,
+}
+
+#[derive(Serialize)]
+struct PromptEdge<'a> {
+    source: &'a str,
+    relation: &'a str,
+    target: &'a str,
+}
+
+const SYSTEM_PROMPT: &str = r#"You answer questions about a personal knowledge base of short notes ("mashes").
+
+Each user turn is JSON with the question, the mashes retrieved for it (id, type, summary, context, memo, creation time in Unix milliseconds) and the relations between them (source, relation, target).
+
+Answer using only these mashes. Cite every mash you rely on by writing its full id in square brackets, e.g. [3f2a...], right after the statement it supports. If the mashes do not answer the question, say so plainly instead of guessing. Answer in plain text, in the language of the question."#;
+
+fn truncate(text: &str) -> String {
+    text.chars().take(MAX_FIELD_CHARS).collect()
+}
+
+/// IDs written in square brackets, in order of first appearance, limited to
+/// the mashes the model was shown. Brackets may hold several comma-separated IDs.
+fn extract_citations(answer: &str, known: &HashSet<&str>) -> Vec<String> {
+    let mut cited = Vec::new();
+    for part in answer.split('[').skip(1) {
+        let Some((inside, _)) = part.split_once(']') else {
+            continue;
+        };
+        for id in inside.split([',', ';', ' ']).map(str::trim) {
+            if known.contains(id) && !cited.iter().any(|c| c == id) {
+                cited.push(id.to_string());
+            }
+        }
+    }
+    cited
+}
+
+/// Answer a question from the retrieved neighborhood, continuing the given
+/// conversation. The answer streams through `on_delta`; citations outside the
+/// neighborhood are dropped.
+pub async fn answer_question(
+    config: &EmbeddingConfig,
+    history: &[ConversationMessage],
+    question: &str,
+    context: &GraphData,
+    cancel: &AtomicBool,
+    on_delta: impl FnMut(&str),
+) -> Result<Answer, String> {
+    let prompt = PromptContext {
+        question,
+        mashes: context
+            .nodes
+            .iter()
+            .map(|n| PromptMash {
+                id: &n.id,
+                mash_type: &n.node_type,
+                summary: &n.summary,
+                context: truncate(&n.context),
+                memo: truncate(&n.memo),
+                created_at: n.created_at,
+            })
+            .collect(),
+        edges: context
+            .edges
+            .iter()
+            .map(|e| PromptEdge {
+                source: &e.source_id,
+                relation: &e.relation_type,
+                target: &e.target_id,
+            })
+            .collect(),
+    };
+    let prompt_json = serde_json::to_string(&prompt).map_err(|e| e.to_string())?;
+
+    let mut messages = vec![ChatMessage::new("system", SYSTEM_PROMPT)];
+    let skip = history.len().saturating_sub(MAX_HISTORY_MESSAGES);
+    for turn in &history[skip..] {
+        messages.push(ChatMessage::new(&turn.role, turn.content.clone()));
+    }
+    messages.push(ChatMessage::new("user", prompt_json));
+
+    let answer = stream_chat(config, &messages, 0.2, cancel, on_delta).await?;
+    log::info!("AI ask response: {} chars", answer.len());
+
+    let known: HashSet<&str> = context.nodes.iter().map(|n| n.id.as_str()).collect();
+    let cited_ids = extract_citations(&answer, &known);
+    Ok(Answer { answer, cited_ids })
+}
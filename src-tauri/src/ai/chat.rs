@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ai::embedding::{EmbeddingConfig, EmbeddingProvider};
+use crate::ai::usage::{record as record_usage, Usage};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatMessage {
+    pub role: String,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn new(role: &str, content: impl Into<String>) -> Self {
+        Self {
+            role: role.to_string(),
+            content: content.into(),
+        }
+    }
+}
+
+/// Reply format requested from the model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatFormat {
+    Text,
+    /// A single JSON object (`response_format: json_object`).
+    Json,
+}
+
+#[derive(Serialize)]
+struct CompletionRequest<'a> {
+    model: &'a str,
+    temperature: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<ResponseFormat>,
+    messages: &'a [ChatMessage],
+}
+
+#[derive(Serialize)]
+struct ResponseFormat {
+    #[serde(rename = "type")]
+    format_type: &'static str,
+}
+
+#[derive(Deserialize)]
+struct CompletionResponse {
+    choices: Vec<CompletionChoice>,
+    usage: Option<Usage>,
+}
+
+#[derive(Deserialize)]
+struct CompletionChoice {
+    message: CompletionMessage,
+}
+
+#[derive(Deserialize)]
+struct CompletionMessage {
+    content: Option<String>,
+}
+
+#[derive(Serialize)]
+struct StreamRequest<'a> {
+    model: &'a str,
+    temperature: f64,
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
+    messages: &'a [ChatMessage],
+}
+
+#[derive(Serialize)]
+struct StreamOptions {
+    include_usage: bool,
+}
+
+#[derive(Deserialize)]
+struct StreamChunk {
+    #[serde(default)]
+    choices: Vec<StreamChoice>,
+    /// Only on the final chunk, and only when the provider honours `include_usage`.
+    usage: Option<Usage>,
+}
+
+#[derive(Deserialize)]
+struct StreamChoice {
+    delta: StreamDelta,
+}
+
+#[derive(Deserialize)]
+struct StreamDelta {
+    content: Option<String>,
+}
+
+/// Cancellation flags for in-flight streams, keyed by the request ID the front
+/// end chose.
+#[derive(Default)]
+pub struct ChatStreams(Mutex<HashMap<String, Arc<AtomicBool>>>);
+
+impl ChatStreams {
+    pub fn register(&self, request_id: &str) -> Arc<AtomicBool> {
+        let flag = Arc::new(AtomicBool::new(false));
+        if let Ok(mut streams) = self.0.lock() {
+            streams.insert(request_id.to_string(), flag.clone());
+        }
+        flag
+    }
+
+    /// Returns false when no stream with that ID is running.
+    pub fn cancel(&self, request_id: &str) -> bool {
+        match self.0.lock().ok().and_then(|s| s.get(request_id).cloned()) {
+            Some(flag) => {
+                flag.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn finish(&self, request_id: &str) {
+        if let Ok(mut streams) = self.0.lock() {
+            streams.remove(request_id);
+        }
+    }
+}
+
+/// Both providers speak the OpenAI chat completions protocol.
+fn chat_url(provider: &EmbeddingProvider) -> &'static str {
+    match provider {
+        EmbeddingProvider::OpenAI => "https://api.openai.com/v1/chat/completions",
+        EmbeddingProvider::Gemini => {
+            "https://generativelanguage.googleapis.com/v1beta/openai/chat/completions"
+        }
+    }
+}
+
+/// Apply one SSE line to the running completion. Returns false once the
+/// server signals the end of the stream.
+fn handle_sse_line(
+    line: &str,
+    text: &mut String,
+    usage: &mut Option<Usage>,
+    on_delta: &mut impl FnMut(&str),
+) -> bool {
+    let Some(data) = line.strip_prefix("data:") else {
+        return true;
+    };
+    let data = data.trim();
+    if data == "[DONE]" {
+        return false;
+    }
+    match serde_json::from_str::<StreamChunk>(data) {
+        Ok(chunk) => {
+            if chunk.usage.is_some() {
+                *usage = chunk.usage;
+            }
+            for choice in chunk.choices {
+                if let Some(delta) = choice.delta.content.filter(|d| !d.is_empty()) {
+                    on_delta(&delta);
+                    text.push_str(&delta);
+                }
+            }
+        }
+        Err(e) => log::warn!("Skipping malformed stream chunk: {}", e),
+    }
+    true
+}
+
+/// Resolves once `flag` is set; polled so a stalled stream can still be cancelled.
+async fn cancelled(flag: &AtomicBool) {
+    while !flag.load(Ordering::Relaxed) {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+/// Run a chat completion over server-sent events, handing each text delta to
+/// `on_delta` as it arrives. Setting `cancel` drops the connection and fails
+/// with "Cancelled"; otherwise the full text is returned.
+pub async fn stream_chat(
+    config: &EmbeddingConfig,
+    messages: &[ChatMessage],
+    temperature: f64,
+    cancel: &AtomicBool,
+    mut on_delta: impl FnMut(&str),
+) -> Result<String, String> {
+    let url = chat_url(&config.provider);
+    let request = StreamRequest {
+        model: &config.chat_model,
+        temperature,
+        stream: true,
+        stream_options: match config.provider {
+            EmbeddingProvider::OpenAI => Some(StreamOptions { include_usage: true }),
+            EmbeddingProvider::Gemini => None,
+        },
+        messages,
+    };
+    let prompt_chars: usize = messages.iter().map(|m| m.content.chars().count()).sum();
+
+    let client = reqwest::Client::new();
+    let mut response = client
+        .post(url)
+        .header("Authorization", format!("Bearer {}", config.api_key))
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| format!("Chat API request failed: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Chat API error {}: {}", status, body));
+    }
+
+    // Chunks can end mid-line or mid-character, so only complete lines are decoded.
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut text = String::new();
+    let mut usage: Option<Usage> = None;
+    // Whatever was streamed is billed, so account for it however the stream ends.
+    let finish = |text: &str, usage: Option<Usage>| {
+        let usage = usage.unwrap_or_else(|| Usage::estimate(prompt_chars, text.chars().count()));
+        record_usage(config, "chat", &config.chat_model, &usage);
+    };
+    loop {
+        let chunk = tokio::select! {
+            chunk = response.chunk() => chunk.map_err(|e| format!("Chat stream failed: {}", e)),
+            _ = cancelled(cancel) => Err("Cancelled".to_string()),
+        };
+        let chunk = match chunk {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => break,
+            Err(e) => {
+                finish(&text, usage);
+                return Err(e);
+            }
+        };
+        buffer.extend_from_slice(&chunk);
+        while let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            if !handle_sse_line(line.trim_end(), &mut text, &mut usage, &mut on_delta) {
+                finish(&text, usage);
+                return Ok(text);
+            }
+        }
+    }
+    let rest = String::from_utf8_lossy(&buffer).to_string();
+    handle_sse_line(rest.trim_end(), &mut text, &mut usage, &mut on_delta);
+    finish(&text, usage);
+    Ok(text)
+}
+
+/// Run a chat completion and return the whole reply at once. Usage is recorded
+/// under `config`'s sink, estimated from the text when the provider omits it.
+pub async fn complete_chat(
+    config: &EmbeddingConfig,
+    messages: &[ChatMessage],
+    temperature: f64,
+    format: ChatFormat,
+) -> Result<String, String> {
+    let request = CompletionRequest {
+        model: &config.chat_model,
+        temperature,
+        response_format: match format {
+            ChatFormat::Text => None,
+            ChatFormat::Json => Some(ResponseFormat {
+                format_type: "json_object",
+            }),
+        },
+        messages,
+    };
+    let prompt_chars: usize = messages.iter().map(|m| m.content.chars().count()).sum();
+
+    let client = reqwest::Client::new();
+    let response = client
+        .post(chat_url(&config.provider))
+        .header("Authorization", format!("Bearer {}", config.api_key))
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| format!("Chat API request failed: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Chat API error {}: {}", status, body));
+    }
+
+    let result: CompletionResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse chat response: {}", e))?;
+
+    let content = result
+        .choices
+        .into_iter()
+        .next()
+        .and_then(|c| c.message.content)
+        .ok_or_else(|| "No response content".to_string())?;
+    let usage = result
+        .usage
+        .unwrap_or_else(|| Usage::estimate(prompt_chars, content.chars().count()));
+    record_usage(config, "chat", &config.chat_model, &usage);
+    Ok(content)
+}
@@ -1,7 +1,8 @@
 use serde::Deserialize;
-use serde::Serialize;
 
-use crate::ai::embedding::{EmbeddingConfig, EmbeddingProvider};
+use crate::ai::chat::{complete_chat, ChatFormat, ChatMessage};
+use crate::ai::embedding::EmbeddingConfig;
+use crate::models::MashTypeDef;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct ClassifyResponse {
@@ -12,46 +13,11 @@ pub struct ClassifyResponse {
     pub memo: Option<String>,
 }
 
-#[derive(Serialize)]
-struct ChatRequest {
-    model: String,
-    temperature: f64,
-    response_format: ResponseFormat,
-    messages: Vec<Message>,
-}
-
-#[derive(Serialize)]
-struct ResponseFormat {
-    #[serde(rename = "type")]
-    format_type: String,
-}
-
-#[derive(Serialize)]
-struct Message {
-    role: String,
-    content: String,
-}
-
-#[derive(Deserialize)]
-struct ChatApiResponse {
-    choices: Vec<Choice>,
-}
-
-#[derive(Deserialize)]
-struct Choice {
-    message: ChoiceMessage,
-}
-
-#[derive(Deserialize)]
-struct ChoiceMessage {
-    content: Option<String>,
-}
-
 const SYSTEM_PROMPT: &str = r#"You are a knowledge classification assistant for a personal knowledge base.
 Given raw text input from the user, extract and return a structured JSON object.
 
 Rules:
-- "type": one of "결정" (decision made), "문제" (problem/issue to solve), "인사이트" (insight/discovery), "질문" (question needing discussion)
+- "type": one of {types}
 - "summary": a concise one-line summary of the input (same language as input)
 - "context": background context extracted from the input, or null if none
 - "memo": any additional notes or details, or null if none
@@ -61,72 +27,43 @@ Return JSON: { "type": string, "summary": string, "context": string | null, "mem
 If there is no distinct context or memo extractable from the input, set them to null.
 Do not fabricate information — only extract what is actually present in the text."#;
 
-const VALID_TYPES: &[&str] = &["결정", "문제", "인사이트", "질문"];
+/// Render the configured taxonomy into the system prompt, e.g.
+/// `"decision" (a decision that has been made), "problem" (...)`.
+fn build_system_prompt(types: &[MashTypeDef]) -> String {
+    let list = types
+        .iter()
+        .map(|t| format!("\"{}\" ({})", t.code, t.description))
+        .collect::<Vec<_>>()
+        .join(", ");
+    SYSTEM_PROMPT.replace("{types}", &list)
+}
 
 pub async fn classify_text(
     config: &EmbeddingConfig,
+    types: &[MashTypeDef],
     text: &str,
 ) -> Result<ClassifyResponse, String> {
-    let url = match config.provider {
-        EmbeddingProvider::OpenAI => "https://api.openai.com/v1/chat/completions",
-        EmbeddingProvider::Gemini => {
-            "https://generativelanguage.googleapis.com/v1beta/openai/chat/completions"
-        }
-    };
-
-    let request = ChatRequest {
-        model: config.chat_model.clone(),
-        temperature: 0.3,
-        response_format: ResponseFormat {
-            format_type: "json_object".to_string(),
-        },
-        messages: vec![
-            Message {
-                role: "system".to_string(),
-                content: SYSTEM_PROMPT.to_string(),
-            },
-            Message {
-                role: "user".to_string(),
-                content: text.to_string(),
-            },
-        ],
-    };
-
-    let client = reqwest::Client::new();
-    let response = client
-        .post(url)
-        .header("Authorization", format!("Bearer {}", config.api_key))
-        .json(&request)
-        .send()
-        .await
-        .map_err(|e| format!("Chat API request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        return Err(format!("Chat API error {}: {}", status, body));
+    if types.is_empty() {
+        return Err("No mash types configured".to_string());
     }
 
-    let result: ChatApiResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse chat response: {}", e))?;
-
-    let content = result
-        .choices
-        .first()
-        .and_then(|c| c.message.content.as_ref())
-        .ok_or_else(|| "No response content".to_string())?;
+    let messages = [
+        ChatMessage::new("system", build_system_prompt(types)),
+        ChatMessage::new("user", text),
+    ];
+    let content = complete_chat(config, &messages, 0.3, ChatFormat::Json).await?;
 
     log::info!("AI classify response: {}", content);
 
-    let parsed: ClassifyResponse = serde_json::from_str(content)
+    let parsed: ClassifyResponse = serde_json::from_str(&content)
         .map_err(|e| format!("Failed to parse AI classification response: {}", e))?;
 
-    if !VALID_TYPES.contains(&parsed.mash_type.as_str()) {
+    if !types.iter().any(|t| t.code == parsed.mash_type) {
+        let codes: Vec<&str> = types.iter().map(|t| t.code.as_str()).collect();
         return Err(format!(
-            "Invalid mash type from AI: '{}'. Expected one of: 결정, 문제, 인사이트, 질문",
-            parsed.mash_type
+            "Invalid mash type from AI: '{}'. Expected one of: {}",
+            parsed.mash_type,
+            codes.join(", ")
         ));
     }
 
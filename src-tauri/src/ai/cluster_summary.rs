@@ -0,0 +1,70 @@
+use serde::Deserialize;
+
+use crate::ai::chat::{complete_chat, ChatFormat, ChatMessage};
+use crate::ai::embedding::EmbeddingConfig;
+use crate::ai::usage::Usage;
+
+/// Upper bound on member summaries sent per cluster to keep the prompt small.
+const MAX_MEMBERS_IN_PROMPT: usize = 40;
+
+/// Rough length of the answer (a short title and 2-3 sentences).
+const COMPLETION_CHARS: usize = 600;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClusterSummary {
+    pub title: String,
+    pub summary: String,
+}
+
+const SYSTEM_PROMPT: &str = r#"You name and summarize topics in a personal knowledge base.
+
+You receive the summaries of knowledge items that were grouped into one topic cluster.
+
+Rules:
+- "title": a short topic label of 2-6 words
+- "summary": 2-3 sentences synthesizing what the items collectively say, including notable decisions, open problems or tensions
+- Write in the same language as the items; if mixed, use the majority language
+- Do not fabricate information that is not present in the items
+
+Return JSON: { "title": string, "summary": string }"#;
+
+fn member_list(member_summaries: &[String]) -> String {
+    member_summaries
+        .iter()
+        .take(MAX_MEMBERS_IN_PROMPT)
+        .map(|s| format!("- {}", s))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Estimated usage of summarizing a cluster, for budget checks.
+pub fn estimate_summary_usage(member_summaries: &[String]) -> Usage {
+    let prompt_chars = SYSTEM_PROMPT.chars().count() + member_list(member_summaries).chars().count();
+    Usage::estimate(prompt_chars, COMPLETION_CHARS)
+}
+
+pub async fn summarize_cluster(
+    config: &EmbeddingConfig,
+    member_summaries: &[String],
+) -> Result<ClusterSummary, String> {
+    if member_summaries.is_empty() {
+        return Err("Cluster has no members to summarize".to_string());
+    }
+
+    let items = member_list(member_summaries);
+
+    let messages = [
+        ChatMessage::new("system", SYSTEM_PROMPT),
+        ChatMessage::new("user", items),
+    ];
+    let content = complete_chat(config, &messages, 0.3, ChatFormat::Json).await?;
+
+    log::info!("AI cluster summary response: {}", content);
+
+    let parsed: ClusterSummary = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse AI cluster summary: {}", e))?;
+    if parsed.title.trim().is_empty() {
+        return Err("AI returned an empty cluster title".to_string());
+    }
+    Ok(parsed)
+}
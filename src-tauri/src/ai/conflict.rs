@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ai::chat::{complete_chat, ChatFormat, ChatMessage};
+use crate::ai::embedding::EmbeddingConfig;
+use crate::db::conflicts::ConflictSide;
+
+/// The model's verdict on a conflicting pair.
+#[derive(Debug, Clone)]
+pub struct ConflictJudgement {
+    /// ID of the mash that supersedes the other, or None when both still stand.
+    pub superseding_id: Option<String>,
+    pub rationale: String,
+}
+
+#[derive(Deserialize)]
+struct AIJudgement {
+    supersedes: Option<String>,
+    rationale: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PromptItem<'a> {
+    label: &'a str,
+    #[serde(rename = "type")]
+    mash_type: &'a str,
+    summary: &'a str,
+    context: &'a str,
+    created_at: i64,
+}
+
+const SYSTEM_PROMPT: &str = r#"You review contradictions in a personal knowledge base.
+
+You receive two items, "A" and "B", that were marked as conflicting. Each has a type, a summary, optional context and a creation time (Unix milliseconds).
+
+Decide whether one item supersedes the other, e.g. a later decision that replaces an earlier one, or a finding that invalidates a claim.
+- "supersedes": "A" if A supersedes B, "B" if B supersedes A, or null if both still stand and the conflict is unresolved
+- "rationale": one or two sentences explaining the verdict, written in the same language as the items
+
+Newer is not automatically better; only pick a side when the content supports it.
+
+Return JSON: { "supersedes": "A" | "B" | null, "rationale": string }"#;
+
+pub async fn judge_conflict(
+    config: &EmbeddingConfig,
+    a: &ConflictSide,
+    b: &ConflictSide,
+) -> Result<ConflictJudgement, String> {
+    let items = [("A", a), ("B", b)]
+        .into_iter()
+        .map(|(label, side)| PromptItem {
+            label,
+            mash_type: &side.mash_type,
+            summary: &side.summary,
+            context: &side.context,
+            created_at: side.created_at,
+        })
+        .collect::<Vec<_>>();
+    let items_json = serde_json::to_string(&items).map_err(|e| e.to_string())?;
+
+    let messages = [
+        ChatMessage::new("system", SYSTEM_PROMPT),
+        ChatMessage::new("user", items_json),
+    ];
+    let content = complete_chat(config, &messages, 0.1, ChatFormat::Json).await?;
+
+    log::info!("AI conflict response: {}", content);
+
+    let parsed: AIJudgement = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse AI conflict verdict: {}", e))?;
+    let superseding_id = match parsed.supersedes.as_deref() {
+        Some("A") => Some(a.id.clone()),
+        Some("B") => Some(b.id.clone()),
+        _ => None,
+    };
+    Ok(ConflictJudgement {
+        superseding_id,
+        rationale: parsed.rationale,
+    })
+}
@@ -0,0 +1,102 @@
+use serde::Serialize;
+
+use crate::ai::chat::{complete_chat, ChatFormat, ChatMessage};
+use crate::ai::embedding::EmbeddingConfig;
+use crate::db::digests::{DigestConflict, DigestInput, DigestMash};
+
+/// Keeps the prompt bounded in busy weeks.
+const MAX_ITEMS_PER_SECTION: usize = 40;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PromptDigest<'a> {
+    period_start: &'a str,
+    period_end: &'a str,
+    new_mashes: Vec<PromptMash<'a>>,
+    most_central: Vec<PromptMash<'a>>,
+    new_conflicts: Vec<PromptConflict<'a>>,
+    open_questions: Vec<PromptMash<'a>>,
+}
+
+#[derive(Serialize)]
+struct PromptMash<'a> {
+    #[serde(rename = "type")]
+    mash_type: &'a str,
+    summary: &'a str,
+    context: &'a str,
+}
+
+#[derive(Serialize)]
+struct PromptConflict<'a> {
+    a: &'a str,
+    b: &'a str,
+    rationale: Option<&'a str>,
+}
+
+const SYSTEM_PROMPT: &str = r###"You write a periodic digest of a personal knowledge base.
+
+You receive JSON with the period (local dates), the items ("mashes") added in that period, the most central of them in the knowledge graph, newly detected conflicts between items, and open questions that nothing supports yet.
+
+Write the digest in Markdown:
+- A "# " title naming the period
+- A short overview paragraph of the main themes
+- "## Key ideas" covering the most central new items and why they matter
+- "## New items" grouping the rest by theme
+- "## Conflicts" summarizing each conflict in one line
+- "## Open questions" listing the questions worth revisiting
+
+Leave out a section when it has nothing to report. Use only the information given. Write in the language the items are written in. Return only the Markdown."###;
+
+fn to_prompt(mashes: &[DigestMash]) -> Vec<PromptMash<'_>> {
+    mashes
+        .iter()
+        .take(MAX_ITEMS_PER_SECTION)
+        .map(|m| PromptMash {
+            mash_type: &m.mash_type,
+            summary: &m.summary,
+            context: &m.context,
+        })
+        .collect()
+}
+
+fn conflicts_to_prompt(conflicts: &[DigestConflict]) -> Vec<PromptConflict<'_>> {
+    conflicts
+        .iter()
+        .take(MAX_ITEMS_PER_SECTION)
+        .map(|c| PromptConflict {
+            a: &c.source,
+            b: &c.target,
+            rationale: c.rationale.as_deref(),
+        })
+        .collect()
+}
+
+/// Ask the chat model to write the digest as Markdown.
+pub async fn write_digest(config: &EmbeddingConfig, input: &DigestInput) -> Result<String, String> {
+    let prompt = PromptDigest {
+        period_start: &input.period_start,
+        period_end: &input.period_end,
+        new_mashes: to_prompt(&input.new_mashes),
+        most_central: to_prompt(&input.central),
+        new_conflicts: conflicts_to_prompt(&input.conflicts),
+        open_questions: to_prompt(&input.open_questions),
+    };
+    let prompt_json = serde_json::to_string(&prompt).map_err(|e| e.to_string())?;
+
+    let messages = [
+        ChatMessage::new("system", SYSTEM_PROMPT),
+        ChatMessage::new("user", prompt_json),
+    ];
+    let markdown = complete_chat(config, &messages, 0.4, ChatFormat::Text).await?;
+    log::info!("AI digest response: {} chars", markdown.len());
+
+    // Some models wrap the whole document in a code fence.
+    let trimmed = markdown.trim();
+    let unfenced = trimmed
+        .strip_prefix("```markdown")
+        .or_else(|| trimmed.strip_prefix("```md"))
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|rest| rest.strip_suffix("```"))
+        .unwrap_or(trimmed);
+    Ok(unfenced.trim().to_string())
+}
@@ -1,6 +1,7 @@
 use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
 
+use crate::ai::usage::{record as record_usage, Usage, UsageSink};
 use crate::db;
 
 // --- Provider types ---
@@ -40,6 +41,27 @@ pub struct EmbeddingConfig {
     pub api_key: String,
     pub embedding_model: String,
     pub chat_model: String,
+    /// Usage accounting for calls made with this config; see `tracked`.
+    pub usage: Option<UsageSink>,
+}
+
+/// Output size requested from Gemini; OpenAI models return their native size.
+const GEMINI_OUTPUT_DIMENSIONS: u32 = 1536;
+
+/// Dimensions requested for `provider`, or 0 when the model decides.
+pub fn requested_dimensions(provider: &EmbeddingProvider) -> u32 {
+    match provider {
+        EmbeddingProvider::OpenAI => 0,
+        EmbeddingProvider::Gemini => GEMINI_OUTPUT_DIMENSIONS,
+    }
+}
+
+/// Texts sent per embedding request: OpenAI accepts 2048, Gemini 100.
+pub fn embedding_batch_size(provider: &EmbeddingProvider) -> usize {
+    match provider {
+        EmbeddingProvider::OpenAI => 2048,
+        EmbeddingProvider::Gemini => 100,
+    }
 }
 
 pub fn default_embedding_model(provider: &EmbeddingProvider) -> &'static str {
@@ -80,6 +102,7 @@ pub fn resolve_embedding_config(conn: &Connection) -> Result<EmbeddingConfig, St
         api_key,
         embedding_model,
         chat_model,
+        usage: None,
     })
 }
 
@@ -95,13 +118,8 @@ pub async fn generate_embeddings(
     }
 
     match config.provider {
-        EmbeddingProvider::OpenAI => {
-            generate_openai_embeddings(&config.api_key, &config.embedding_model, texts).await
-        }
-        EmbeddingProvider::Gemini => {
-            generate_gemini_embeddings(&config.api_key, &config.embedding_model, texts, task_type)
-                .await
-        }
+        EmbeddingProvider::OpenAI => generate_openai_embeddings(config, texts).await,
+        EmbeddingProvider::Gemini => generate_gemini_embeddings(config, texts, task_type).await,
     }
 }
 
@@ -116,6 +134,7 @@ struct OpenAIEmbeddingRequest {
 #[derive(Deserialize)]
 struct OpenAIEmbeddingResponse {
     data: Vec<OpenAIEmbeddingData>,
+    usage: Option<Usage>,
 }
 
 #[derive(Deserialize)]
@@ -123,16 +142,17 @@ struct OpenAIEmbeddingData {
     embedding: Vec<f32>,
 }
 
+/// Each request is recorded as soon as it succeeds, so chunks billed before a
+/// later one fails are still accounted for.
 async fn generate_openai_embeddings(
-    api_key: &str,
-    model: &str,
+    config: &EmbeddingConfig,
     texts: Vec<String>,
 ) -> Result<Vec<Option<Vec<f32>>>, String> {
+    let (api_key, model) = (&config.api_key, &config.embedding_model);
     let client = reqwest::Client::new();
     let mut all_results: Vec<Option<Vec<f32>>> = Vec::with_capacity(texts.len());
 
-    // OpenAI batch limit: 2048 per request
-    for chunk in texts.chunks(2048) {
+    for chunk in texts.chunks(embedding_batch_size(&EmbeddingProvider::OpenAI)) {
         let request = OpenAIEmbeddingRequest {
             model: model.to_string(),
             input: chunk.to_vec(),
@@ -152,11 +172,17 @@ async fn generate_openai_embeddings(
             return Err(format!("Embedding API error {}: {}", status, body));
         }
 
-        let result: OpenAIEmbeddingResponse = response
-            .json()
-            .await
-            .map_err(|e| format!("Failed to parse embedding response: {}", e))?;
+        let chars: usize = chunk.iter().map(|t| t.chars().count()).sum();
+        let result: OpenAIEmbeddingResponse = match response.json().await {
+            Ok(result) => result,
+            Err(e) => {
+                record_usage(config, "embedding", model, &Usage::estimate(chars, 0));
+                return Err(format!("Failed to parse embedding response: {}", e));
+            }
+        };
 
+        let usage = result.usage.unwrap_or_else(|| Usage::estimate(chars, 0));
+        record_usage(config, "embedding", model, &usage);
         for d in result.data {
             all_results.push(Some(d.embedding));
         }
@@ -203,11 +229,11 @@ struct GeminiEmbeddingValues {
 }
 
 async fn generate_gemini_embeddings(
-    api_key: &str,
-    model: &str,
+    config: &EmbeddingConfig,
     texts: Vec<String>,
     task_type: EmbeddingTaskType,
 ) -> Result<Vec<Option<Vec<f32>>>, String> {
+    let (api_key, model) = (&config.api_key, &config.embedding_model);
     let client = reqwest::Client::new();
     let task_type_str = match task_type {
         EmbeddingTaskType::Document => "RETRIEVAL_DOCUMENT",
@@ -222,8 +248,7 @@ async fn generate_gemini_embeddings(
 
     let mut all_results: Vec<Option<Vec<f32>>> = Vec::with_capacity(texts.len());
 
-    // Gemini batch limit: 100 per request
-    for chunk in texts.chunks(100) {
+    for chunk in texts.chunks(embedding_batch_size(&EmbeddingProvider::Gemini)) {
         let requests: Vec<GeminiEmbedRequest> = chunk
             .iter()
             .map(|text| GeminiEmbedRequest {
@@ -232,7 +257,7 @@ async fn generate_gemini_embeddings(
                     parts: vec![GeminiPart { text: text.clone() }],
                 },
                 task_type: task_type_str.to_string(),
-                output_dimensionality: 1536,
+                output_dimensionality: GEMINI_OUTPUT_DIMENSIONS,
             })
             .collect();
 
@@ -256,6 +281,10 @@ async fn generate_gemini_embeddings(
             return Err(format!("Gemini Embedding API error {}: {}", status, body));
         }
 
+        // The batch endpoint reports no token counts; record the request once it succeeded.
+        let chars: usize = chunk.iter().map(|t| t.chars().count()).sum();
+        record_usage(config, "embedding", model, &Usage::estimate(chars, 0));
+
         let result: GeminiBatchResponse = response
             .json()
             .await
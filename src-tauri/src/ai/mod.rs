@@ -1,3 +1,10 @@
+pub mod ask;
+pub mod chat;
 pub mod classify;
+pub mod cluster_summary;
+pub mod conflict;
+pub mod digest;
 pub mod embedding;
 pub mod relationship;
+pub mod split;
+pub mod usage;
@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 
-use crate::ai::embedding::{EmbeddingConfig, EmbeddingProvider};
+use crate::ai::chat::{complete_chat, ChatFormat, ChatMessage};
+use crate::ai::embedding::EmbeddingConfig;
+use crate::ai::usage::Usage;
+use crate::db::relation_types::resolve_relation_label;
+use crate::models::RelationTypeDef;
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -9,6 +13,9 @@ pub struct RelationCandidate {
     pub source_summary: String,
     pub target_id: String,
     pub target_summary: String,
+    /// Structural evidence that proposed the pair; carried onto the edge, not sent to the model.
+    #[serde(skip)]
+    pub evidence: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -17,41 +24,8 @@ pub struct ExtractedRelation {
     pub target_id: String,
     pub relation: String,
     pub confidence: f64,
-}
-
-#[derive(Serialize)]
-struct ChatRequest {
-    model: String,
-    temperature: f64,
-    response_format: ResponseFormat,
-    messages: Vec<Message>,
-}
-
-#[derive(Serialize)]
-struct ResponseFormat {
-    #[serde(rename = "type")]
-    format_type: String,
-}
-
-#[derive(Serialize)]
-struct Message {
-    role: String,
-    content: String,
-}
-
-#[derive(Deserialize)]
-struct ChatResponse {
-    choices: Vec<Choice>,
-}
-
-#[derive(Deserialize)]
-struct Choice {
-    message: ChoiceMessage,
-}
-
-#[derive(Deserialize)]
-struct ChoiceMessage {
-    content: Option<String>,
+    pub rationale: Option<String>,
+    pub evidence: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -67,126 +41,139 @@ struct AIRelation {
     target_id: String,
     relation: Option<String>,
     confidence: f64,
+    #[serde(default)]
+    rationale: Option<String>,
 }
 
-const VALID_RELATIONS: &[&str] = &["RELATED_TO", "SUPPORTS", "CONFLICTS_WITH"];
+const SYSTEM_PROMPT: &str = r#"You classify relationships between knowledge items in a personal knowledge base.
 
-pub async fn extract_relations(
-    config: &EmbeddingConfig,
-    candidates: Vec<RelationCandidate>,
-) -> Result<Vec<ExtractedRelation>, String> {
-    if candidates.is_empty() {
-        return Ok(vec![]);
-    }
+For each pair, determine the relationship type:
+{relations}
+- null: completely unrelated items with no connection
 
-    let mut results = Vec::new();
+Return JSON: { "relations": [{ "sourceId": string, "targetId": string, "relation": string | null, "confidence": number (0.0-1.0), "rationale": string }] }
 
-    // Process in batches of 5
-    for batch in candidates.chunks(5) {
-        match extract_batch(config, batch).await {
-            Ok(extracted) => results.extend(extracted),
-            Err(e) => {
-                log::warn!("Relationship extraction batch failed, skipping: {}", e);
-            }
-        }
-    }
+"rationale" is one short sentence explaining why the relation holds (or why the items are unrelated), written in the same language as the items.
+
+{origin}{related_to} Only use null when items are truly unrelated."#;
+
+/// Candidate pairs sent per chat request.
+pub const BATCH_SIZE: usize = 5;
 
-    Ok(results)
+/// Rough length of one pair's answer (relation, confidence and a rationale).
+const COMPLETION_CHARS_PER_PAIR: usize = 220;
+
+/// Render the relation registry into the system prompt. Directed relations also
+/// advertise their inverse label so the model can answer in either direction.
+/// How the batch's pairs were found decides what the model is told to expect.
+fn build_system_prompt(
+    relation_types: &[RelationTypeDef],
+    batch: &[RelationCandidate],
+) -> String {
+    let lines = relation_types
+        .iter()
+        .map(|t| match (t.directed, t.inverse_label.as_deref()) {
+            (true, Some(inverse)) => format!(
+                "- \"{}\": {} (directed; use \"{}\" when the target stands in this relation to the source)",
+                t.code, t.description, inverse
+            ),
+            _ => format!("- \"{}\": {} (symmetric)", t.code, t.description),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    // The catch-all hint only makes sense while RELATED_TO is registered
+    let related_to = if relation_types.iter().any(|t| t.code == "RELATED_TO") {
+        " Use RELATED_TO generously for items in the same domain."
+    } else {
+        ""
+    };
+    let structural = batch.iter().filter(|c| c.evidence.is_some()).count();
+    let origin = if structural == 0 {
+        "These items were pre-filtered by semantic similarity, so most pairs likely have some connection."
+    } else if structural == batch.len() {
+        "These pairs were proposed from the graph's structure (shared neighbours, chains), not from their text, so judge each pair on its content."
+    } else {
+        "Some pairs were pre-filtered by semantic similarity and others proposed from the graph's structure, so judge each pair on its content."
+    };
+    SYSTEM_PROMPT
+        .replace("{relations}", &lines)
+        .replace("{origin}", origin)
+        .replace("{related_to}", related_to)
+}
+
+/// Estimated usage of sending `batch` to `extract_batch`, for dry runs and
+/// budget checks.
+pub fn estimate_batch_usage(
+    relation_types: &[RelationTypeDef],
+    batch: &[RelationCandidate],
+) -> Result<Usage, String> {
+    let pairs_json = serde_json::to_string(batch).map_err(|e| e.to_string())?;
+    let prompt_chars =
+        build_system_prompt(relation_types, batch).chars().count() + pairs_json.chars().count();
+    Ok(Usage::estimate(prompt_chars, batch.len() * COMPLETION_CHARS_PER_PAIR))
 }
 
 pub async fn extract_batch(
     config: &EmbeddingConfig,
+    relation_types: &[RelationTypeDef],
     batch: &[RelationCandidate],
 ) -> Result<Vec<ExtractedRelation>, String> {
-    let pairs_json = serde_json::to_string(batch).map_err(|e| e.to_string())?;
-
-    let url = match config.provider {
-        EmbeddingProvider::OpenAI => "https://api.openai.com/v1/chat/completions",
-        EmbeddingProvider::Gemini => {
-            "https://generativelanguage.googleapis.com/v1beta/openai/chat/completions"
-        }
-    };
-
-    let request = ChatRequest {
-        model: config.chat_model.clone(),
-        temperature: 0.1,
-        response_format: ResponseFormat {
-            format_type: "json_object".to_string(),
-        },
-        messages: vec![
-            Message {
-                role: "system".to_string(),
-                content: r#"You classify relationships between knowledge items in a personal knowledge base.
-
-For each pair, determine the relationship type:
-- "RELATED_TO": items share a common topic, context, or are part of the same domain/project
-- "SUPPORTS": source reinforces, extends, or provides evidence for target
-- "CONFLICTS_WITH": source contradicts or creates tension with target
-- null: completely unrelated items with no connection
-
-Return JSON: { "relations": [{ "sourceId": string, "targetId": string, "relation": string | null, "confidence": number (0.0-1.0) }] }
-
-These items were pre-filtered by semantic similarity, so most pairs likely have some connection. Use RELATED_TO generously for items in the same domain. Only use null when items are truly unrelated."#.to_string(),
-            },
-            Message {
-                role: "user".to_string(),
-                content: pairs_json,
-            },
-        ],
-    };
-
-    let client = reqwest::Client::new();
-    let response = client
-        .post(url)
-        .header("Authorization", format!("Bearer {}", config.api_key))
-        .json(&request)
-        .send()
-        .await
-        .map_err(|e| format!("Chat API request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        return Err(format!("Chat API error {}: {}", status, body));
+    if relation_types.is_empty() {
+        return Err("No relation types configured".to_string());
     }
 
-    let result: ChatResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse chat response: {}", e))?;
+    let pairs_json = serde_json::to_string(batch).map_err(|e| e.to_string())?;
 
-    let content = result
-        .choices
-        .first()
-        .and_then(|c| c.message.content.as_ref())
-        .ok_or_else(|| "No response content".to_string())?;
+    let messages = [
+        ChatMessage::new("system", build_system_prompt(relation_types, batch)),
+        ChatMessage::new("user", pairs_json),
+    ];
+    let content = complete_chat(config, &messages, 0.1, ChatFormat::Json).await?;
 
     log::info!("AI response: {}", content);
 
     let parsed: AIResponse =
-        serde_json::from_str(content).map_err(|e| format!("Failed to parse AI response: {}", e))?;
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse AI response: {}", e))?;
 
     // Build set of valid IDs from the input candidates
     let valid_ids: std::collections::HashSet<&str> = batch
         .iter()
         .flat_map(|c| [c.source_id.as_str(), c.target_id.as_str()])
         .collect();
+    let evidence_for = |a: &str, b: &str| {
+        batch
+            .iter()
+            .find(|c| {
+                (c.source_id == a && c.target_id == b) || (c.source_id == b && c.target_id == a)
+            })
+            .and_then(|c| c.evidence.clone())
+    };
 
     Ok(parsed
         .relations
         .into_iter()
         .filter(|r| {
-            r.relation.is_some()
-                && VALID_RELATIONS.contains(&r.relation.as_deref().unwrap_or(""))
-                && r.confidence > 0.0
+            r.confidence > 0.0
                 && valid_ids.contains(r.source_id.as_str())
                 && valid_ids.contains(r.target_id.as_str())
         })
-        .map(|r| ExtractedRelation {
-            source_id: r.source_id,
-            target_id: r.target_id,
-            relation: r.relation.unwrap(),
-            confidence: r.confidence,
+        .filter_map(|r| {
+            let label = r.relation?;
+            let (code, inverted) = resolve_relation_label(relation_types, &label)?;
+            let (source_id, target_id) = if inverted {
+                (r.target_id, r.source_id)
+            } else {
+                (r.source_id, r.target_id)
+            };
+            let evidence = evidence_for(&source_id, &target_id);
+            Some(ExtractedRelation {
+                source_id,
+                target_id,
+                relation: code.to_string(),
+                confidence: r.confidence,
+                rationale: r.rationale.filter(|s| !s.trim().is_empty()),
+                evidence,
+            })
         })
         .collect())
 }
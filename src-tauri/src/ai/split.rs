@@ -0,0 +1,138 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ai::chat::{complete_chat, ChatFormat, ChatMessage};
+use crate::ai::embedding::EmbeddingConfig;
+use crate::models::{Mash, MashTypeDef, SplitPart};
+
+/// An edge of the mash being split, described from the mash's side.
+pub struct SplitNeighbor {
+    pub edge_id: i64,
+    pub relation_type: String,
+    /// True when the mash being split is the edge's source.
+    pub outgoing: bool,
+    pub summary: String,
+}
+
+#[derive(Deserialize)]
+struct AISplit {
+    parts: Vec<AIPart>,
+}
+
+#[derive(Deserialize)]
+struct AIPart {
+    #[serde(rename = "type")]
+    mash_type: String,
+    summary: String,
+    context: Option<String>,
+    memo: Option<String>,
+    #[serde(default)]
+    edges: Vec<i64>,
+}
+
+#[derive(Serialize)]
+struct PromptMash<'a> {
+    #[serde(rename = "type")]
+    mash_type: &'a str,
+    summary: &'a str,
+    context: &'a str,
+    memo: &'a str,
+    edges: Vec<PromptEdge<'a>>,
+}
+
+#[derive(Serialize)]
+struct PromptEdge<'a> {
+    id: i64,
+    relation: &'a str,
+    direction: &'a str,
+    summary: &'a str,
+}
+
+const SYSTEM_PROMPT: &str = r#"You split captures in a personal knowledge base into atomic items.
+
+You receive one item with a type, summary, context, memo and the edges linking it to other items. Each edge has an id, a relation, a direction ("outgoing" when this item is the source) and the other item's summary.
+
+Break the item into the separate ideas it contains. For each part return:
+- "type": one of {types}
+- "summary": a concise one-line summary (same language as the input)
+- "context": background context for this part, or null if none
+- "memo": additional notes for this part, or null if none
+- "edges": ids of the edges that concern this part; an edge may belong to several parts or to none
+
+Every piece of information must land in exactly one part. Do not fabricate information. If the item holds a single idea, return it as one part.
+
+Return JSON: { "parts": [{ "type": string, "summary": string, "context": string | null, "memo": string | null, "edges": number[] }] }"#;
+
+/// Render the configured taxonomy into the system prompt.
+fn build_system_prompt(types: &[MashTypeDef]) -> String {
+    let list = types
+        .iter()
+        .map(|t| format!("\"{}\" ({})", t.code, t.description))
+        .collect::<Vec<_>>()
+        .join(", ");
+    SYSTEM_PROMPT.replace("{types}", &list)
+}
+
+/// Ask the chat model to break a mash into atomic parts. Unknown types fall back
+/// to the original's type and edge ids the model invented are dropped.
+pub async fn split_mash(
+    config: &EmbeddingConfig,
+    types: &[MashTypeDef],
+    mash: &Mash,
+    neighbors: &[SplitNeighbor],
+) -> Result<Vec<SplitPart>, String> {
+    if types.is_empty() {
+        return Err("No mash types configured".to_string());
+    }
+
+    let prompt = PromptMash {
+        mash_type: &mash.mash_type,
+        summary: &mash.summary,
+        context: &mash.context,
+        memo: &mash.memo,
+        edges: neighbors
+            .iter()
+            .map(|n| PromptEdge {
+                id: n.edge_id,
+                relation: &n.relation_type,
+                direction: if n.outgoing { "outgoing" } else { "incoming" },
+                summary: &n.summary,
+            })
+            .collect(),
+    };
+    let prompt_json = serde_json::to_string(&prompt).map_err(|e| e.to_string())?;
+
+    let messages = [
+        ChatMessage::new("system", build_system_prompt(types)),
+        ChatMessage::new("user", prompt_json),
+    ];
+    let content = complete_chat(config, &messages, 0.2, ChatFormat::Json).await?;
+
+    log::info!("AI split response: {}", content);
+
+    let parsed: AISplit = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse AI split response: {}", e))?;
+
+    let known_edges: HashSet<i64> = neighbors.iter().map(|n| n.edge_id).collect();
+    let parts = parsed
+        .parts
+        .into_iter()
+        .filter(|p| !p.summary.trim().is_empty())
+        .map(|p| SplitPart {
+            mash_type: if types.iter().any(|t| t.code == p.mash_type) {
+                p.mash_type
+            } else {
+                mash.mash_type.clone()
+            },
+            summary: p.summary,
+            context: p.context.unwrap_or_default(),
+            memo: p.memo.unwrap_or_default(),
+            edge_ids: p.edges.into_iter().filter(|id| known_edges.contains(id)).collect(),
+        })
+        .collect::<Vec<_>>();
+    if parts.is_empty() {
+        return Err("AI returned no parts".to_string());
+    }
+    Ok(parts)
+}
@@ -0,0 +1,80 @@
+use std::sync::{Arc, Mutex};
+
+use rusqlite::Connection;
+use serde::Deserialize;
+
+use crate::ai::embedding::EmbeddingConfig;
+
+/// Token counts as reported in a response's `usage` object. Providers that
+/// report nothing get an estimate from the text length instead.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Usage {
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
+    #[serde(skip)]
+    pub estimated: bool,
+}
+
+impl Usage {
+    /// Rough token count for text the provider did not meter (~4 characters a token).
+    pub fn estimate(prompt_chars: usize, completion_chars: usize) -> Self {
+        Self {
+            prompt_tokens: prompt_chars.div_ceil(4) as u64,
+            completion_tokens: completion_chars.div_ceil(4) as u64,
+            estimated: true,
+        }
+    }
+
+    pub fn add(&mut self, other: &Usage) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.estimated |= other.estimated;
+    }
+}
+
+/// Where provider calls made with a config are accounted: the database and the
+/// pipeline phase (or command) that made them.
+#[derive(Debug, Clone)]
+pub struct UsageSink {
+    pub conn: Arc<Mutex<Connection>>,
+    pub phase: String,
+    pub run_id: Option<String>,
+}
+
+impl EmbeddingConfig {
+    /// A copy of this config whose provider calls are recorded under `phase`.
+    pub fn tracked(&self, conn: &Arc<Mutex<Connection>>, phase: &str, run_id: Option<&str>) -> Self {
+        Self {
+            usage: Some(UsageSink {
+                conn: conn.clone(),
+                phase: phase.to_string(),
+                run_id: run_id.map(|r| r.to_string()),
+            }),
+            ..self.clone()
+        }
+    }
+}
+
+/// Persist one provider call. Untracked configs record nothing, and a failed
+/// write is only logged so accounting never breaks the call itself.
+pub fn record(config: &EmbeddingConfig, kind: &str, model: &str, usage: &Usage) {
+    let Some(sink) = &config.usage else {
+        return;
+    };
+    let result = sink.conn.lock().map_err(|e| e.to_string()).and_then(|conn| {
+        crate::db::usage::record_usage(
+            &conn,
+            config.provider.as_str(),
+            model,
+            kind,
+            &sink.phase,
+            sink.run_id.as_deref(),
+            usage,
+        )
+    });
+    if let Err(e) = result {
+        log::warn!("Failed to record API usage: {}", e);
+    }
+}
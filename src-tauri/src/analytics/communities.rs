@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+
+use crate::analytics::Graph;
+
+const MAX_LOCAL_PASSES: usize = 32;
+const MIN_GAIN: f64 = 1e-10;
+
+/// Louvain community detection over the weighted undirected graph.
+/// Nodes are visited in index order (ids sorted), so the same graph always
+/// produces the same partition. Returns the members of each community that has
+/// at least two nodes; isolated mashes belong to no community.
+pub fn detect_communities(graph: &Graph) -> Vec<Vec<String>> {
+    let membership = louvain(&graph.weights);
+
+    let mut groups: HashMap<usize, Vec<String>> = HashMap::new();
+    for (i, &c) in membership.iter().enumerate() {
+        groups.entry(c).or_default().push(graph.ids[i].clone());
+    }
+    let mut communities: Vec<Vec<String>> = groups
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .collect();
+    // Members are already sorted; order communities by size, then first member.
+    communities.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].cmp(&b[0])));
+    communities
+}
+
+/// Returns the community index of every node.
+fn louvain(weights: &[Vec<(usize, f64)>]) -> Vec<usize> {
+    let n = weights.len();
+    let mut membership: Vec<usize> = (0..n).collect();
+    // Adjacency of the current level. Self-loops hold the community's internal
+    // weight counted from both ends, so a node's degree is a plain row sum.
+    let mut adj: Vec<Vec<(usize, f64)>> = weights.to_vec();
+
+    loop {
+        let (community, count) = local_moving(&adj);
+        if count == adj.len() {
+            break;
+        }
+        for c in membership.iter_mut() {
+            *c = community[*c];
+        }
+        adj = aggregate(&adj, &community, count);
+    }
+    membership
+}
+
+/// Move nodes between neighbouring communities while modularity improves.
+/// Returns the compacted community of each node and the number of communities.
+fn local_moving(adj: &[Vec<(usize, f64)>]) -> (Vec<usize>, usize) {
+    let n = adj.len();
+    let degree: Vec<f64> = adj
+        .iter()
+        .map(|row| row.iter().map(|&(_, w)| w).sum())
+        .collect();
+    let total: f64 = degree.iter().sum();
+
+    let mut community: Vec<usize> = (0..n).collect();
+    if total <= 0.0 {
+        return (community, n);
+    }
+    let mut community_degree = degree.clone();
+    let mut links_to: Vec<f64> = vec![0.0; n];
+    let mut touched: Vec<usize> = Vec::new();
+
+    for _ in 0..MAX_LOCAL_PASSES {
+        let mut moved = false;
+        for i in 0..n {
+            let current = community[i];
+            community_degree[current] -= degree[i];
+
+            touched.clear();
+            touched.push(current);
+            for &(j, w) in &adj[i] {
+                if j == i {
+                    continue;
+                }
+                let c = community[j];
+                if links_to[c] == 0.0 {
+                    touched.push(c);
+                }
+                links_to[c] += w;
+            }
+
+            // Ties keep the node where it is, so repeated runs settle on the same partition.
+            let gain = |c: usize| links_to[c] - community_degree[c] * degree[i] / total;
+            let mut best = current;
+            let mut best_gain = gain(current);
+            for &c in &touched[1..] {
+                let g = gain(c);
+                if g > best_gain + MIN_GAIN {
+                    best = c;
+                    best_gain = g;
+                }
+            }
+
+            community_degree[best] += degree[i];
+            if best != current {
+                community[i] = best;
+                moved = true;
+            }
+            for &c in &touched {
+                links_to[c] = 0.0;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+
+    // Renumber in order of first appearance.
+    let mut renumber: HashMap<usize, usize> = HashMap::new();
+    for c in community.iter_mut() {
+        let next = renumber.len();
+        *c = *renumber.entry(*c).or_insert(next);
+    }
+    let count = renumber.len();
+    (community, count)
+}
+
+/// Collapse each community into a single node of the next level.
+fn aggregate(adj: &[Vec<(usize, f64)>], community: &[usize], count: usize) -> Vec<Vec<(usize, f64)>> {
+    let mut rows: Vec<HashMap<usize, f64>> = vec![HashMap::new(); count];
+    for (i, row) in adj.iter().enumerate() {
+        let ci = community[i];
+        for &(j, w) in row {
+            *rows[ci].entry(community[j]).or_insert(0.0) += w;
+        }
+    }
+    rows.into_iter()
+        .map(|row| {
+            let mut row: Vec<(usize, f64)> = row.into_iter().collect();
+            row.sort_unstable_by_key(|&(c, _)| c);
+            row
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two triangles joined by one weak edge.
+    fn two_triangles() -> Graph {
+        let edges = [
+            (0, 1, 1.0),
+            (1, 2, 1.0),
+            (0, 2, 1.0),
+            (3, 4, 1.0),
+            (4, 5, 1.0),
+            (3, 5, 1.0),
+            (2, 3, 0.1),
+        ];
+        let mut weights = vec![Vec::new(); 6];
+        for &(a, b, w) in &edges {
+            weights[a].push((b, w));
+            weights[b].push((a, w));
+        }
+        Graph {
+            ids: ["a", "b", "c", "d", "e", "f"].iter().map(|s| s.to_string()).collect(),
+            neighbors: weights.iter().map(|row| row.iter().map(|&(j, _)| j).collect()).collect(),
+            out_links: weights.clone(),
+            weights,
+        }
+    }
+
+    #[test]
+    fn splits_at_the_weak_bridge() {
+        assert_eq!(
+            detect_communities(&two_triangles()),
+            vec![vec!["a", "b", "c"], vec!["d", "e", "f"]]
+        );
+    }
+
+    #[test]
+    fn same_graph_gives_same_partition() {
+        let graph = two_triangles();
+        let first = detect_communities(&graph);
+        for _ in 0..5 {
+            assert_eq!(detect_communities(&graph), first);
+        }
+    }
+
+    #[test]
+    fn isolated_nodes_belong_to_no_community() {
+        let mut graph = two_triangles();
+        graph.ids.push("g".to_string());
+        graph.neighbors.push(Vec::new());
+        graph.out_links.push(Vec::new());
+        graph.weights.push(Vec::new());
+        let communities = detect_communities(&graph);
+        assert!(communities.iter().all(|c| !c.contains(&"g".to_string())));
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::analytics::Graph;
+use crate::models::NodeMetrics;
+
+const PAGERANK_DAMPING: f64 = 0.85;
+const PAGERANK_TOLERANCE: f64 = 1e-6;
+const PAGERANK_MAX_ITER: usize = 100;
+
+/// Above this size betweenness is estimated from a fixed set of pivot sources
+/// (Brandes & Pich) instead of a BFS from every node.
+const EXACT_BETWEENNESS_MAX_NODES: usize = 2_000;
+const BETWEENNESS_PIVOTS: usize = 500;
+
+/// Compute per-node centrality scores, ordered by PageRank (most central first).
+pub fn compute_metrics(graph: &Graph) -> Vec<NodeMetrics> {
+    let n = graph.node_count();
+    if n == 0 {
+        return vec![];
+    }
+
+    let betweenness = betweenness(graph);
+    let pagerank = pagerank(graph);
+    let clustering = clustering(graph);
+
+    // Hubs: degree at least two standard deviations above the mean.
+    let degrees: Vec<f64> = graph.neighbors.iter().map(|l| l.len() as f64).collect();
+    let mean = degrees.iter().sum::<f64>() / n as f64;
+    let variance = degrees.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / n as f64;
+    let hub_threshold = (mean + 2.0 * variance.sqrt()).max(3.0);
+
+    let mut metrics: Vec<NodeMetrics> = (0..n)
+        .map(|i| NodeMetrics {
+            id: graph.ids[i].clone(),
+            degree: graph.neighbors[i].len() as u32,
+            betweenness: betweenness[i],
+            pagerank: pagerank[i],
+            clustering: clustering[i],
+            is_hub: degrees[i] >= hub_threshold,
+        })
+        .collect();
+    metrics.sort_by(|a, b| b.pagerank.total_cmp(&a.pagerank));
+    metrics
+}
+
+/// PageRank alone, keyed by mash ID, for callers that need no other metric.
+pub fn compute_pagerank(graph: &Graph) -> HashMap<String, f64> {
+    if graph.node_count() == 0 {
+        return HashMap::new();
+    }
+    graph.ids.iter().cloned().zip(pagerank(graph)).collect()
+}
+
+/// Brandes' algorithm on the undirected graph, normalized to 0..1.
+fn betweenness(graph: &Graph) -> Vec<f64> {
+    let n = graph.node_count();
+    let mut scores = vec![0.0f64; n];
+    if n < 3 {
+        return scores;
+    }
+
+    let sources: Vec<usize> = if n <= EXACT_BETWEENNESS_MAX_NODES {
+        (0..n).collect()
+    } else {
+        // Evenly strided pivots keep results stable between calls.
+        (0..BETWEENNESS_PIVOTS)
+            .map(|k| k * n / BETWEENNESS_PIVOTS)
+            .collect()
+    };
+
+    let mut stack: Vec<usize> = Vec::with_capacity(n);
+    let mut queue: VecDeque<usize> = VecDeque::with_capacity(n);
+    let mut sigma = vec![0.0f64; n];
+    let mut dist = vec![-1i64; n];
+    let mut delta = vec![0.0f64; n];
+
+    for &s in &sources {
+        stack.clear();
+        sigma.fill(0.0);
+        dist.fill(-1);
+        delta.fill(0.0);
+        sigma[s] = 1.0;
+        dist[s] = 0;
+        queue.push_back(s);
+
+        while let Some(v) = queue.pop_front() {
+            stack.push(v);
+            for &w in &graph.neighbors[v] {
+                if dist[w] < 0 {
+                    dist[w] = dist[v] + 1;
+                    queue.push_back(w);
+                }
+                if dist[w] == dist[v] + 1 {
+                    sigma[w] += sigma[v];
+                }
+            }
+        }
+
+        // Predecessors are the neighbors one step closer to the source,
+        // so they can be recovered from `dist` without storing lists.
+        while let Some(w) = stack.pop() {
+            for &v in &graph.neighbors[w] {
+                if dist[v] >= 0 && dist[v] + 1 == dist[w] {
+                    delta[v] += sigma[v] / sigma[w] * (1.0 + delta[w]);
+                }
+            }
+            if w != s {
+                scores[w] += delta[w];
+            }
+        }
+    }
+
+    // Each pair is counted from both ends; pivots are scaled up to the full node set.
+    let scale = n as f64 / sources.len() as f64 / 2.0;
+    let pairs = ((n - 1) * (n - 2)) as f64 / 2.0;
+    for score in &mut scores {
+        *score = *score * scale / pairs;
+    }
+    scores
+}
+
+/// Weighted PageRank over directed out-links. Dangling nodes spread their rank evenly.
+fn pagerank(graph: &Graph) -> Vec<f64> {
+    let n = graph.node_count();
+    let uniform = 1.0 / n as f64;
+    let out_weight: Vec<f64> = graph
+        .out_links
+        .iter()
+        .map(|links| links.iter().map(|&(_, w)| w).sum())
+        .collect();
+
+    let mut rank = vec![uniform; n];
+    let mut next = vec![0.0f64; n];
+    for _ in 0..PAGERANK_MAX_ITER {
+        let dangling: f64 = (0..n)
+            .filter(|&i| out_weight[i] <= 0.0)
+            .map(|i| rank[i])
+            .sum();
+        let base = (1.0 - PAGERANK_DAMPING) * uniform + PAGERANK_DAMPING * dangling * uniform;
+        next.iter_mut().for_each(|r| *r = base);
+
+        for (v, links) in graph.out_links.iter().enumerate() {
+            if out_weight[v] <= 0.0 {
+                continue;
+            }
+            let share = PAGERANK_DAMPING * rank[v] / out_weight[v];
+            for &(t, w) in links {
+                next[t] += share * w;
+            }
+        }
+
+        let diff: f64 = rank.iter().zip(&next).map(|(a, b)| (a - b).abs()).sum();
+        std::mem::swap(&mut rank, &mut next);
+        if diff < PAGERANK_TOLERANCE {
+            break;
+        }
+    }
+    rank
+}
+
+/// Local clustering coefficient: the share of a node's neighbor pairs that are linked.
+fn clustering(graph: &Graph) -> Vec<f64> {
+    let n = graph.node_count();
+    let mut marked = vec![usize::MAX; n];
+    let mut scores = vec![0.0f64; n];
+
+    for (v, neighbors) in graph.neighbors.iter().enumerate() {
+        let k = neighbors.len();
+        if k < 2 {
+            continue;
+        }
+        for &u in neighbors {
+            marked[u] = v;
+        }
+        let mut links = 0usize;
+        for &u in neighbors {
+            links += graph.neighbors[u]
+                .iter()
+                .filter(|&&w| w > u && marked[w] == v)
+                .count();
+        }
+        scores[v] = 2.0 * links as f64 / (k * (k - 1)) as f64;
+    }
+    scores
+}
@@ -0,0 +1,122 @@
+pub mod communities;
+pub mod link_prediction;
+pub mod metrics;
+pub mod paths;
+
+use std::collections::HashMap;
+
+use rusqlite::Connection;
+
+/// Compact in-memory view of the JARRED graph. Nodes are addressed by index so
+/// the algorithms can work on plain vectors instead of string maps.
+pub struct Graph {
+    pub ids: Vec<String>,
+    /// Undirected neighbor lists; parallel edges of different relation types collapse into one.
+    pub neighbors: Vec<Vec<usize>>,
+    /// Directed out-links weighted by summed confidence. Symmetric relations point both ways.
+    pub out_links: Vec<Vec<(usize, f64)>>,
+    /// Undirected adjacency weighted by confidence and relation type, for community detection.
+    pub weights: Vec<Vec<(usize, f64)>>,
+}
+
+/// How strongly a relation ties two mashes into the same topic. Conflicts still
+/// share a subject but pull less than agreement; custom relations count fully.
+fn relation_weight(relation_type: &str) -> f64 {
+    match relation_type {
+        "SUPPORTS" => 1.0,
+        "RELATED_TO" => 0.8,
+        "CONFLICTS_WITH" => 0.5,
+        _ => 1.0,
+    }
+}
+
+/// Sort a link list by target and sum the weights of duplicates.
+fn merge_links(links: &mut Vec<(usize, f64)>) {
+    links.sort_unstable_by_key(|&(t, _)| t);
+    let mut merged: Vec<(usize, f64)> = Vec::with_capacity(links.len());
+    for &(t, w) in links.iter() {
+        match merged.last_mut() {
+            Some(last) if last.0 == t => last.1 += w,
+            _ => merged.push((t, w)),
+        }
+    }
+    *links = merged;
+}
+
+impl Graph {
+    pub fn node_count(&self) -> usize {
+        self.ids.len()
+    }
+}
+
+/// Load every JARRED mash and the edges between them.
+pub fn load_graph(conn: &Connection) -> Result<Graph, String> {
+    let mut stmt = conn
+        .prepare("SELECT id FROM mashes WHERE status = 'JARRED' ORDER BY id")
+        .map_err(|e| e.to_string())?;
+    let ids: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    let index: HashMap<&str, usize> = ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| (id.as_str(), i))
+        .collect();
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT e.source_id, e.target_id, e.relation_type, e.confidence, COALESCE(rt.directed, 0)
+             FROM edges e
+             LEFT JOIN relation_types rt ON rt.code = e.relation_type
+             WHERE e.source_id IN (SELECT id FROM mashes WHERE status = 'JARRED')
+               AND e.target_id IN (SELECT id FROM mashes WHERE status = 'JARRED')",
+        )
+        .map_err(|e| e.to_string())?;
+    let edges: Vec<(String, String, String, f64, bool)> = stmt
+        .query_map([], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let n = ids.len();
+    let mut neighbors: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut out_links: Vec<Vec<(usize, f64)>> = vec![Vec::new(); n];
+    let mut weights: Vec<Vec<(usize, f64)>> = vec![Vec::new(); n];
+    for (source_id, target_id, relation_type, confidence, directed) in &edges {
+        let (Some(&s), Some(&t)) = (index.get(source_id.as_str()), index.get(target_id.as_str()))
+        else {
+            continue;
+        };
+        if s == t {
+            continue;
+        }
+        neighbors[s].push(t);
+        neighbors[t].push(s);
+        out_links[s].push((t, *confidence));
+        if !directed {
+            out_links[t].push((s, *confidence));
+        }
+        let weight = confidence * relation_weight(relation_type);
+        weights[s].push((t, weight));
+        weights[t].push((s, weight));
+    }
+
+    for list in &mut neighbors {
+        list.sort_unstable();
+        list.dedup();
+    }
+    for links in out_links.iter_mut().chain(weights.iter_mut()) {
+        merge_links(links);
+    }
+
+    Ok(Graph {
+        ids,
+        neighbors,
+        out_links,
+        weights,
+    })
+}
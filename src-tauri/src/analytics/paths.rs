@@ -0,0 +1,351 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use rusqlite::Connection;
+
+use crate::models::GraphPath;
+
+/// Confidence floor so that a near-zero edge is expensive but still passable.
+const MIN_CONFIDENCE: f64 = 0.05;
+
+/// Undirected graph for path search. Each neighbor keeps only its cheapest edge.
+pub struct PathGraph {
+    ids: Vec<String>,
+    index: HashMap<String, usize>,
+    /// (neighbor, edge id, cost)
+    adj: Vec<Vec<(usize, i64, f64)>>,
+}
+
+/// Load JARRED mashes and their edges for path search, optionally leaving out
+/// CONFLICTS_WITH edges.
+pub fn load_path_graph(conn: &Connection, skip_conflicts: bool) -> Result<PathGraph, String> {
+    let mut stmt = conn
+        .prepare("SELECT id FROM mashes WHERE status = 'JARRED' ORDER BY id")
+        .map_err(|e| e.to_string())?;
+    let ids: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, source_id, target_id, confidence FROM edges
+             WHERE (?1 = 0 OR relation_type != 'CONFLICTS_WITH')
+             ORDER BY id",
+        )
+        .map_err(|e| e.to_string())?;
+    let edges: Vec<(i64, String, String, f64)> = stmt
+        .query_map([skip_conflicts], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(build_path_graph(ids, edges))
+}
+
+/// Index `ids` and link them by `(edge id, source, target, confidence)` edges.
+fn build_path_graph(ids: Vec<String>, edges: Vec<(i64, String, String, f64)>) -> PathGraph {
+    let index: HashMap<String, usize> = ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| (id.clone(), i))
+        .collect();
+
+    let mut adj: Vec<Vec<(usize, i64, f64)>> = vec![Vec::new(); ids.len()];
+    for (edge_id, source_id, target_id, confidence) in edges {
+        let (Some(&s), Some(&t)) = (index.get(&source_id), index.get(&target_id)) else {
+            continue;
+        };
+        if s == t {
+            continue;
+        }
+        // Every hop costs at least 1, so fewer steps win; low confidence makes a hop dearer.
+        let cost = 1.0 / confidence.max(MIN_CONFIDENCE);
+        adj[s].push((t, edge_id, cost));
+        adj[t].push((s, edge_id, cost));
+    }
+    for list in &mut adj {
+        list.sort_by(|a, b| a.0.cmp(&b.0).then(a.2.total_cmp(&b.2)).then(a.1.cmp(&b.1)));
+        list.dedup_by_key(|&mut (n, _, _)| n);
+    }
+
+    PathGraph { ids, index, adj }
+}
+
+#[derive(PartialEq)]
+struct HeapEntry {
+    cost: f64,
+    node: usize,
+}
+
+impl Eq for HeapEntry {}
+
+impl Ord for HeapEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed for a min-heap; ties break on node index for determinism.
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A path as node indices with the edge taken at each step and the total cost.
+#[derive(Clone)]
+struct Path {
+    nodes: Vec<usize>,
+    edges: Vec<i64>,
+    cost: f64,
+}
+
+fn dijkstra(
+    graph: &PathGraph,
+    source: usize,
+    target: usize,
+    blocked_nodes: &[bool],
+    blocked_edges: &HashSet<i64>,
+) -> Option<Path> {
+    let n = graph.ids.len();
+    let mut dist = vec![f64::INFINITY; n];
+    let mut prev: Vec<Option<(usize, i64)>> = vec![None; n];
+    let mut heap = BinaryHeap::new();
+    dist[source] = 0.0;
+    heap.push(HeapEntry { cost: 0.0, node: source });
+
+    while let Some(HeapEntry { cost, node }) = heap.pop() {
+        if node == target {
+            break;
+        }
+        if cost > dist[node] {
+            continue;
+        }
+        for &(next, edge_id, edge_cost) in &graph.adj[node] {
+            if blocked_nodes[next] || blocked_edges.contains(&edge_id) {
+                continue;
+            }
+            let candidate = cost + edge_cost;
+            if candidate < dist[next] {
+                dist[next] = candidate;
+                prev[next] = Some((node, edge_id));
+                heap.push(HeapEntry { cost: candidate, node: next });
+            }
+        }
+    }
+
+    if !dist[target].is_finite() {
+        return None;
+    }
+    let mut nodes = vec![target];
+    let mut edges = Vec::new();
+    let mut current = target;
+    while let Some((p, edge_id)) = prev[current] {
+        nodes.push(p);
+        edges.push(edge_id);
+        current = p;
+    }
+    nodes.reverse();
+    edges.reverse();
+    Some(Path {
+        nodes,
+        edges,
+        cost: dist[target],
+    })
+}
+
+/// Yen's algorithm: the `k` cheapest loopless paths, cheapest first.
+fn k_shortest_paths(graph: &PathGraph, source: usize, target: usize, k: usize) -> Vec<Path> {
+    let n = graph.ids.len();
+    let no_nodes = vec![false; n];
+    let Some(first) = dijkstra(graph, source, target, &no_nodes, &HashSet::new()) else {
+        return vec![];
+    };
+
+    let mut found: Vec<Path> = vec![first];
+    let mut candidates: Vec<Path> = Vec::new();
+    while found.len() < k {
+        let last = found[found.len() - 1].clone();
+        for i in 0..last.nodes.len() - 1 {
+            let spur = last.nodes[i];
+            let root_nodes = &last.nodes[..=i];
+            let root_edges = &last.edges[..i];
+
+            // Block the next edge of every known path sharing this root,
+            // and the root itself so the spur path stays loopless.
+            let mut blocked_edges = HashSet::new();
+            for p in &found {
+                if p.nodes.len() > i + 1 && p.nodes[..=i] == *root_nodes {
+                    blocked_edges.insert(p.edges[i]);
+                }
+            }
+            let mut blocked_nodes = vec![false; n];
+            for &node in &root_nodes[..i] {
+                blocked_nodes[node] = true;
+            }
+
+            let Some(spur_path) = dijkstra(graph, spur, target, &blocked_nodes, &blocked_edges)
+            else {
+                continue;
+            };
+
+            let root_cost: f64 = root_edges
+                .iter()
+                .zip(root_nodes.windows(2))
+                .map(|(edge_id, pair)| edge_cost(graph, pair[0], *edge_id))
+                .sum();
+            let mut nodes = root_nodes.to_vec();
+            nodes.extend_from_slice(&spur_path.nodes[1..]);
+            let mut edges = root_edges.to_vec();
+            edges.extend_from_slice(&spur_path.edges);
+            let path = Path {
+                nodes,
+                edges,
+                cost: root_cost + spur_path.cost,
+            };
+
+            let known = found.iter().chain(candidates.iter()).any(|p| p.edges == path.edges);
+            if !known {
+                candidates.push(path);
+            }
+        }
+
+        if candidates.is_empty() {
+            break;
+        }
+        let best = candidates
+            .iter()
+            .enumerate()
+            .min_by(|a, b| a.1.cost.total_cmp(&b.1.cost).then(a.1.nodes.len().cmp(&b.1.nodes.len())))
+            .map(|(i, _)| i)
+            .unwrap();
+        found.push(candidates.swap_remove(best));
+    }
+    found
+}
+
+fn edge_cost(graph: &PathGraph, from: usize, edge_id: i64) -> f64 {
+    graph.adj[from]
+        .iter()
+        .find(|&&(_, id, _)| id == edge_id)
+        .map(|&(_, _, cost)| cost)
+        .unwrap_or(0.0)
+}
+
+/// Find up to `k` shortest paths between two JARRED mashes, ignoring edge direction.
+pub fn find_paths(
+    graph: &PathGraph,
+    source_id: &str,
+    target_id: &str,
+    k: usize,
+) -> Result<Vec<GraphPath>, String> {
+    if source_id == target_id {
+        return Err("Source and target must be different mashes".to_string());
+    }
+    let lookup = |id: &str| {
+        graph
+            .index
+            .get(id)
+            .copied()
+            .ok_or_else(|| format!("Mash '{}' is not in the graph", id))
+    };
+    let source = lookup(source_id)?;
+    let target = lookup(target_id)?;
+
+    Ok(k_shortest_paths(graph, source, target, k.max(1))
+        .into_iter()
+        .map(|p| GraphPath {
+            node_ids: p.nodes.iter().map(|&i| graph.ids[i].clone()).collect(),
+            edge_ids: p.edges,
+            cost: p.cost,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(ids: &[&str], edges: &[(i64, &str, &str, f64)]) -> PathGraph {
+        build_path_graph(
+            ids.iter().map(|id| id.to_string()).collect(),
+            edges
+                .iter()
+                .map(|&(id, s, t, c)| (id, s.to_string(), t.to_string(), c))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn paths_come_cheapest_first() {
+        // a-b-d costs 2, a-c-d costs 4, the weak direct a-d edge costs 10
+        let g = graph(
+            &["a", "b", "c", "d"],
+            &[
+                (1, "a", "b", 1.0),
+                (2, "b", "d", 1.0),
+                (3, "a", "c", 0.5),
+                (4, "c", "d", 0.5),
+                (5, "a", "d", 0.1),
+            ],
+        );
+        let paths = find_paths(&g, "a", "d", 5).unwrap();
+        let nodes: Vec<Vec<&str>> = paths
+            .iter()
+            .map(|p| p.node_ids.iter().map(String::as_str).collect())
+            .collect();
+        assert_eq!(nodes, vec![vec!["a", "b", "d"], vec!["a", "c", "d"], vec!["a", "d"]]);
+        assert_eq!(paths[0].edge_ids, vec![1, 2]);
+        assert!(paths.windows(2).all(|w| w[0].cost <= w[1].cost));
+        assert!((paths[2].cost - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn parallel_edges_collapse_to_the_cheapest() {
+        // Both directions of the same pair, plus a weaker duplicate
+        let g = graph(
+            &["a", "b"],
+            &[(1, "a", "b", 0.5), (2, "b", "a", 0.9), (3, "a", "b", 0.2)],
+        );
+        let paths = find_paths(&g, "a", "b", 3).unwrap();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].edge_ids, vec![2]);
+    }
+
+    #[test]
+    fn paths_are_distinct_and_loopless() {
+        // A square with one diagonal gives several routes from a to c
+        let g = graph(
+            &["a", "b", "c", "d"],
+            &[
+                (1, "a", "b", 1.0),
+                (2, "b", "c", 1.0),
+                (3, "c", "d", 1.0),
+                (4, "d", "a", 1.0),
+                (5, "b", "d", 1.0),
+            ],
+        );
+        let paths = find_paths(&g, "a", "c", 10).unwrap();
+        assert_eq!(paths.len(), 4);
+        for (i, p) in paths.iter().enumerate() {
+            let unique: HashSet<&String> = p.node_ids.iter().collect();
+            assert_eq!(unique.len(), p.node_ids.len());
+            assert!(paths[..i].iter().all(|q| q.edge_ids != p.edge_ids));
+        }
+    }
+
+    #[test]
+    fn unknown_or_equal_endpoints_are_errors() {
+        let g = graph(&["a", "b"], &[(1, "a", "b", 1.0)]);
+        assert!(find_paths(&g, "a", "a", 1).is_err());
+        assert!(find_paths(&g, "a", "z", 1).is_err());
+        assert!(find_paths(&graph(&["a", "b"], &[]), "a", "b", 1).unwrap().is_empty());
+    }
+}
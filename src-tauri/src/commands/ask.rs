@@ -0,0 +1,199 @@
+use std::collections::HashSet;
+
+use tauri::{AppHandle, Emitter, State};
+
+use crate::ai;
+use crate::ai::chat::ChatStreams;
+use crate::ai::embedding::{resolve_embedding_config, EmbeddingTaskType};
+use crate::commands::DbState;
+use crate::db;
+use crate::models::{AskResponse, ChatChunk, Conversation, ConversationMessage, GraphData};
+
+/// Event carrying streamed answer text to the front end.
+const CHUNK_EVENT: &str = "chat-chunk";
+
+/// Upper bound on mashes handed to the model per question.
+const MAX_CONTEXT_NODES: usize = 40;
+
+/// Seeds and their direct neighbors, seeds first, capped at `MAX_CONTEXT_NODES`.
+fn gather_context(conn: &rusqlite::Connection, seeds: &[String]) -> Result<GraphData, String> {
+    let mut context = GraphData {
+        nodes: vec![],
+        edges: vec![],
+    };
+    let mut node_ids: HashSet<String> = HashSet::new();
+    let mut edge_ids: HashSet<i64> = HashSet::new();
+    let neighborhoods = seeds
+        .iter()
+        .map(|id| db::edges::expand_node(conn, id, 1))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Every seed before any neighbor, so the cap only ever trims neighbors.
+    for (seed, neighborhood) in seeds.iter().zip(&neighborhoods) {
+        if let Some(node) = neighborhood.nodes.iter().find(|n| &n.id == seed) {
+            if node_ids.insert(node.id.clone()) {
+                context.nodes.push(node.clone());
+            }
+        }
+    }
+    for neighborhood in &neighborhoods {
+        for node in &neighborhood.nodes {
+            if context.nodes.len() >= MAX_CONTEXT_NODES {
+                break;
+            }
+            if node_ids.insert(node.id.clone()) {
+                context.nodes.push(node.clone());
+            }
+        }
+    }
+    for neighborhood in neighborhoods {
+        for edge in neighborhood.edges {
+            if node_ids.contains(&edge.source_id)
+                && node_ids.contains(&edge.target_id)
+                && edge_ids.insert(edge.id)
+            {
+                context.edges.push(edge);
+            }
+        }
+    }
+    Ok(context)
+}
+
+/// Answer a question from the jar shelf: retrieve the closest mashes, widen to
+/// their neighbors, and let the chat model answer with citations. Passing a
+/// `conversation_id` continues that conversation; mashes cited earlier in it
+/// stay in context for follow-ups. The answer streams as `chat-chunk` events
+/// tagged with `request_id`, which `cancel_chat` can use to stop it.
+#[tauri::command]
+pub async fn ask(
+    app: AppHandle,
+    state: State<'_, DbState>,
+    streams: State<'_, ChatStreams>,
+    question: String,
+    conversation_id: Option<String>,
+    request_id: Option<String>,
+) -> Result<AskResponse, String> {
+    let question = question.trim().to_string();
+    if question.is_empty() {
+        return Err("Question is empty".to_string());
+    }
+    let (config, history) = {
+        let conn = state.0.lock().map_err(|e| e.to_string())?;
+        // Checked before any provider call is paid for
+        let history = match &conversation_id {
+            Some(id) if !db::conversations::conversation_exists(&conn, id)? => {
+                return Err(format!("Conversation {} not found", id));
+            }
+            Some(id) => db::conversations::get_messages(&conn, id)?,
+            None => vec![],
+        };
+        (resolve_embedding_config(&conn)?.tracked(&state.0, "ask", None), history)
+    };
+
+    let embedding = ai::embedding::generate_embeddings(
+        &config,
+        vec![question.clone()],
+        EmbeddingTaskType::Query,
+    )
+    .await?
+    .into_iter()
+    .next()
+    .flatten()
+    .ok_or_else(|| "Failed to generate query embedding".to_string())?;
+
+    let context = {
+        let conn = state.0.lock().map_err(|e| e.to_string())?;
+        let threshold = db::settings::get_setting(&conn, "search_threshold")?
+            .and_then(|v| v.parse::<f32>().ok())
+            .unwrap_or(0.3);
+        let top_k = db::settings::get_setting(&conn, "search_top_k")?
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(10);
+        let similar =
+            crate::similarity::find_similar_mashes(&conn, "", &embedding, top_k, threshold)?;
+
+        let mut seeds: Vec<String> = similar.into_iter().map(|s| s.target_id).collect();
+        for message in history.iter().rev() {
+            for id in &message.cited_ids {
+                if !seeds.contains(id) {
+                    seeds.push(id.clone());
+                }
+            }
+        }
+        gather_context(&conn, &seeds)?
+    };
+
+    let request_id = request_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let cancel = streams.register(&request_id);
+    let answer = ai::ask::answer_question(&config, &history, &question, &context, &cancel, |delta| {
+        let _ = app.emit(
+            CHUNK_EVENT,
+            ChatChunk {
+                request_id: request_id.clone(),
+                delta: delta.to_string(),
+            },
+        );
+    })
+    .await;
+    streams.finish(&request_id);
+    let answer = answer?;
+
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    let (conversation_id, message) = db::conversations::add_exchange(
+        &conn,
+        conversation_id,
+        &question,
+        &answer.answer,
+        &answer.cited_ids,
+        &config.chat_model,
+    )?;
+
+    let cited: HashSet<&str> = answer.cited_ids.iter().map(|id| id.as_str()).collect();
+    let graph = GraphData {
+        nodes: context
+            .nodes
+            .iter()
+            .filter(|n| cited.contains(n.id.as_str()))
+            .cloned()
+            .collect(),
+        edges: context
+            .edges
+            .iter()
+            .filter(|e| cited.contains(e.source_id.as_str()) && cited.contains(e.target_id.as_str()))
+            .cloned()
+            .collect(),
+    };
+
+    Ok(AskResponse {
+        conversation_id,
+        message,
+        graph,
+    })
+}
+
+/// Stop a streaming answer; returns false when nothing with that ID is running.
+#[tauri::command]
+pub fn cancel_chat(streams: State<ChatStreams>, request_id: String) -> Result<bool, String> {
+    Ok(streams.cancel(&request_id))
+}
+
+#[tauri::command]
+pub fn get_conversations(state: State<DbState>) -> Result<Vec<Conversation>, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::conversations::get_conversations(&conn)
+}
+
+#[tauri::command]
+pub fn get_conversation_messages(
+    state: State<DbState>,
+    id: String,
+) -> Result<Vec<ConversationMessage>, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::conversations::get_messages(&conn, &id)
+}
+
+#[tauri::command]
+pub fn delete_conversation(state: State<DbState>, id: String) -> Result<(), String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::conversations::delete_conversation(&conn, &id)
+}
@@ -0,0 +1,47 @@
+use tauri::State;
+
+use crate::ai;
+use crate::commands::DbState;
+use crate::db;
+use crate::models::ConflictsReport;
+
+#[tauri::command]
+pub fn get_conflicts_report(state: State<DbState>) -> Result<ConflictsReport, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::conflicts::get_conflicts_report(&conn)
+}
+
+/// Ask the chat model which side of each unjudged conflict supersedes the other,
+/// then return the refreshed report.
+#[tauri::command]
+pub async fn review_conflicts(
+    state: State<'_, DbState>,
+    limit: Option<usize>,
+) -> Result<ConflictsReport, String> {
+    let (config, pending) = {
+        let conn = state.0.lock().map_err(|e| e.to_string())?;
+        (
+            ai::embedding::resolve_embedding_config(&conn)?.tracked(&state.0, "conflicts", None),
+            db::conflicts::get_conflicts_for_review(&conn, limit.unwrap_or(10))?,
+        )
+    };
+
+    for conflict in &pending {
+        match ai::conflict::judge_conflict(&config, &conflict.source, &conflict.target).await {
+            Ok(judgement) => {
+                let conn = state.0.lock().map_err(|e| e.to_string())?;
+                db::conflicts::save_conflict_resolution(
+                    &conn,
+                    conflict.edge_id,
+                    judgement.superseding_id.as_deref(),
+                    &judgement.rationale,
+                    &config.chat_model,
+                )?;
+            }
+            Err(e) => log::warn!("Conflict review failed for edge {}: {}", conflict.edge_id, e),
+        }
+    }
+
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::conflicts::get_conflicts_report(&conn)
+}
@@ -0,0 +1,39 @@
+use tauri::State;
+
+use crate::ai::embedding::resolve_embedding_config;
+use crate::commands::DbState;
+use crate::db;
+use crate::models::Digest;
+use crate::pipeline::digest::{generate_digest, window_days};
+
+#[tauri::command]
+pub fn get_digests(state: State<DbState>) -> Result<Vec<Digest>, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::digests::get_digests(&conn)
+}
+
+/// Write a digest for the last `days` days now, regardless of the schedule.
+/// Defaults to the configured digest window.
+#[tauri::command]
+pub async fn generate_digest_now(
+    state: State<'_, DbState>,
+    days: Option<i64>,
+) -> Result<Digest, String> {
+    let (config, days) = {
+        let conn = state.0.lock().map_err(|e| e.to_string())?;
+        let days = match days {
+            Some(d) if d > 0 => d,
+            _ => window_days(&conn)?,
+        };
+        (resolve_embedding_config(&conn)?.tracked(&state.0, "digest", None), days)
+    };
+    let now = db::now_ms();
+    generate_digest(&state.0, &config, now - days * 24 * 60 * 60 * 1000, now).await
+}
+
+/// A digest's Markdown, for the front end to save where the user picks.
+#[tauri::command]
+pub fn export_digest(state: State<DbState>, id: i64) -> Result<String, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    Ok(db::digests::get_digest(&conn, id)?.markdown)
+}
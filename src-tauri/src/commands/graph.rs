@@ -1,8 +1,12 @@
 use tauri::State;
 
+use crate::analytics;
 use crate::commands::DbState;
 use crate::db;
-use crate::models::{Edge, GraphData, GraphFilters};
+use crate::models::{
+    Cluster, Edge, EdgeReviewItem, GraphData, GraphDiff, GraphFilters, NodeMetrics, PathResult,
+    RelationTypeDef,
+};
 
 #[tauri::command]
 pub fn get_graph(
@@ -13,6 +17,18 @@ pub fn get_graph(
     db::edges::get_graph(&conn, &filters)
 }
 
+#[tauri::command(async)]
+pub fn get_graph_at(state: State<DbState>, timestamp: i64) -> Result<GraphData, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::history::get_graph_at(&conn, timestamp)
+}
+
+#[tauri::command(async)]
+pub fn diff_graph(state: State<DbState>, from: i64, to: i64) -> Result<GraphDiff, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::history::diff_graph(&conn, from, to)
+}
+
 #[tauri::command]
 pub fn get_node_detail(
     state: State<DbState>,
@@ -42,7 +58,15 @@ pub fn add_edge(
     confidence: f64,
 ) -> Result<Edge, String> {
     let conn = state.0.lock().map_err(|e| e.to_string())?;
-    db::edges::add_edge(&conn, &source_id, &target_id, &relation_type, &source, confidence)
+    let relation_types = db::relation_types::get_relation_types(&conn)?;
+    let (code, inverted) = db::relation_types::resolve_relation_label(&relation_types, &relation_type)
+        .ok_or_else(|| format!("Unknown relation type: '{}'", relation_type))?;
+    let (source_id, target_id) = if inverted {
+        (target_id, source_id)
+    } else {
+        (source_id, target_id)
+    };
+    db::edges::add_edge(&conn, &source_id, &target_id, code, &source, confidence)
 }
 
 #[tauri::command]
@@ -53,6 +77,9 @@ pub fn update_edge(
     confidence: Option<f64>,
 ) -> Result<Edge, String> {
     let conn = state.0.lock().map_err(|e| e.to_string())?;
+    if let Some(ref rt) = relation_type {
+        db::relation_types::get_relation_type(&conn, rt)?;
+    }
     db::edges::update_edge(&conn, id, relation_type.as_deref(), confidence)
 }
 
@@ -75,6 +102,9 @@ pub fn update_node(
     memo: Option<String>,
 ) -> Result<crate::models::Mash, String> {
     let conn = state.0.lock().map_err(|e| e.to_string())?;
+    if let Some(ref t) = mash_type {
+        db::mash_types::ensure_mash_type(&conn, t)?;
+    }
     db::mashes::update_mash(
         &conn,
         &id,
@@ -84,3 +114,102 @@ pub fn update_node(
         memo.as_deref(),
     )
 }
+
+#[tauri::command]
+pub fn get_relation_types(state: State<DbState>) -> Result<Vec<RelationTypeDef>, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::relation_types::get_relation_types(&conn)
+}
+
+#[tauri::command]
+pub fn save_relation_type(
+    state: State<DbState>,
+    code: String,
+    description: String,
+    directed: bool,
+    inverse_label: Option<String>,
+) -> Result<RelationTypeDef, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::relation_types::save_relation_type(
+        &conn,
+        &code,
+        &description,
+        directed,
+        inverse_label.as_deref(),
+    )
+}
+
+#[tauri::command]
+pub fn delete_relation_type(state: State<DbState>, code: String) -> Result<(), String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::relation_types::delete_relation_type(&conn, &code)
+}
+
+#[tauri::command]
+pub fn get_pending_edges(
+    state: State<DbState>,
+    limit: Option<usize>,
+) -> Result<Vec<EdgeReviewItem>, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::edge_review::get_pending_edges(&conn, limit.unwrap_or(100))
+}
+
+#[tauri::command]
+pub fn accept_edge(state: State<DbState>, id: i64) -> Result<(), String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::edge_review::accept_edge(&conn, id)
+}
+
+#[tauri::command]
+pub fn reject_edge(state: State<DbState>, id: i64) -> Result<(), String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::edge_review::reject_edge(&conn, id)
+}
+
+#[tauri::command(async)]
+pub fn get_graph_metrics(state: State<DbState>) -> Result<Vec<NodeMetrics>, String> {
+    // Only hold the lock while loading; the computation can take a moment on large graphs.
+    let graph = {
+        let conn = state.0.lock().map_err(|e| e.to_string())?;
+        analytics::load_graph(&conn)?
+    };
+    Ok(analytics::metrics::compute_metrics(&graph))
+}
+
+#[tauri::command]
+pub fn get_clusters(state: State<DbState>) -> Result<Vec<Cluster>, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::clusters::get_clusters(&conn)
+}
+
+#[tauri::command(async)]
+pub fn refresh_clusters(state: State<DbState>) -> Result<Vec<Cluster>, String> {
+    crate::pipeline::cluster::refresh_clusters(&state.0)
+}
+
+#[tauri::command(async)]
+pub fn find_paths(
+    state: State<DbState>,
+    source_id: String,
+    target_id: String,
+    k: Option<usize>,
+    skip_conflicts: Option<bool>,
+) -> Result<PathResult, String> {
+    // Only hold the lock while loading; the search runs Dijkstra once per spur node.
+    let graph = {
+        let conn = state.0.lock().map_err(|e| e.to_string())?;
+        analytics::paths::load_path_graph(&conn, skip_conflicts.unwrap_or(false))?
+    };
+    let paths = analytics::paths::find_paths(&graph, &source_id, &target_id, k.unwrap_or(3))?;
+
+    let mut node_ids: Vec<String> = paths.iter().flat_map(|p| p.node_ids.iter().cloned()).collect();
+    node_ids.sort();
+    node_ids.dedup();
+    let mut edge_ids: Vec<i64> = paths.iter().flat_map(|p| p.edge_ids.iter().copied()).collect();
+    edge_ids.sort_unstable();
+    edge_ids.dedup();
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    let graph = db::edges::get_subgraph(&conn, &node_ids, &edge_ids)?;
+
+    Ok(PathResult { graph, paths })
+}
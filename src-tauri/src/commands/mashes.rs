@@ -1,9 +1,11 @@
+use std::collections::HashMap;
+
 use tauri::State;
 
 use crate::ai;
 use crate::commands::DbState;
 use crate::db;
-use crate::models::Mash;
+use crate::models::{DuplicateCandidate, Mash, MashRevision, MashTypeDef, SplitPart, SplitPreview};
 
 #[tauri::command]
 pub fn get_mashes_by_status(
@@ -24,6 +26,7 @@ pub fn add_mash(
     memo: String,
 ) -> Result<Mash, String> {
     let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::mash_types::ensure_mash_type(&conn, &mash_type)?;
     db::mashes::add_mash(&conn, &mash_type, &summary, &context, &memo)
 }
 
@@ -43,6 +46,9 @@ pub fn update_mash(
     memo: Option<String>,
 ) -> Result<Mash, String> {
     let conn = state.0.lock().map_err(|e| e.to_string())?;
+    if let Some(ref t) = mash_type {
+        db::mash_types::ensure_mash_type(&conn, t)?;
+    }
     db::mashes::update_mash(
         &conn,
         &id,
@@ -69,19 +75,68 @@ pub fn search_mashes(state: State<DbState>, query: String) -> Result<Vec<Mash>,
     db::mashes::search_mashes(&conn, &query)
 }
 
+#[tauri::command(async)]
+pub fn find_duplicates(
+    state: State<DbState>,
+    min_cosine: Option<f64>,
+    min_trigram: Option<f64>,
+    limit: Option<usize>,
+) -> Result<Vec<DuplicateCandidate>, String> {
+    // The pairwise comparison runs without holding the lock.
+    let scan = {
+        let conn = state.0.lock().map_err(|e| e.to_string())?;
+        db::duplicates::load_duplicate_scan(&conn)?
+    };
+    Ok(db::duplicates::find_duplicates_in(
+        &scan,
+        min_cosine.unwrap_or(0.92),
+        min_trigram.unwrap_or(0.6),
+        limit.unwrap_or(50),
+    ))
+}
+
+#[tauri::command]
+pub fn dismiss_duplicate(
+    state: State<DbState>,
+    source_id: String,
+    target_id: String,
+) -> Result<(), String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::duplicates::dismiss_duplicate(&conn, &source_id, &target_id)
+}
+
+#[tauri::command]
+pub fn merge_mashes(
+    state: State<DbState>,
+    survivor_id: String,
+    merged_id: String,
+) -> Result<Mash, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::mashes::merge_mashes(&conn, &survivor_id, &merged_id)
+}
+
+#[tauri::command]
+pub fn get_mash_revisions(state: State<DbState>, id: String) -> Result<Vec<MashRevision>, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::mashes::get_mash_revisions(&conn, &id)
+}
+
 #[tauri::command]
 pub async fn add_mash_with_ai(
     state: State<'_, DbState>,
     text: String,
 ) -> Result<Mash, String> {
-    // 1. Resolve config while holding the lock
-    let config = {
+    // 1. Resolve config and taxonomy while holding the lock
+    let (config, types) = {
         let conn = state.0.lock().map_err(|e| e.to_string())?;
-        ai::embedding::resolve_embedding_config(&conn)?
+        (
+            ai::embedding::resolve_embedding_config(&conn)?.tracked(&state.0, "classify", None),
+            db::mash_types::get_mash_types(&conn)?,
+        )
     };
 
     // 2. Call AI (lock released)
-    let result = ai::classify::classify_text(&config, &text).await?;
+    let result = ai::classify::classify_text(&config, &types, &text).await?;
 
     // 3. Insert into DB
     let conn = state.0.lock().map_err(|e| e.to_string())?;
@@ -93,3 +148,71 @@ pub async fn add_mash_with_ai(
         &result.memo.unwrap_or_default(),
     )
 }
+
+/// Ask the AI how to split a mash; nothing is written until `split_mash`.
+#[tauri::command]
+pub async fn preview_split(state: State<'_, DbState>, id: String) -> Result<SplitPreview, String> {
+    let (config, types, original, edges) = {
+        let conn = state.0.lock().map_err(|e| e.to_string())?;
+        (
+            ai::embedding::resolve_embedding_config(&conn)?.tracked(&state.0, "split", None),
+            db::mash_types::get_mash_types(&conn)?,
+            db::mashes::get_mash(&conn, &id)?,
+            db::edges::get_mash_edges(&conn, &id)?,
+        )
+    };
+
+    let neighbors = edges
+        .iter()
+        .map(|(edge, summary)| ai::split::SplitNeighbor {
+            edge_id: edge.id,
+            relation_type: edge.relation_type.clone(),
+            outgoing: edge.source_id == id,
+            summary: summary.clone(),
+        })
+        .collect::<Vec<_>>();
+    let parts = ai::split::split_mash(&config, &types, &original, &neighbors).await?;
+
+    Ok(SplitPreview {
+        original,
+        edges: edges.into_iter().map(|(edge, _)| edge).collect(),
+        parts,
+    })
+}
+
+#[tauri::command]
+pub fn split_mash(
+    state: State<DbState>,
+    id: String,
+    parts: Vec<SplitPart>,
+) -> Result<Vec<Mash>, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    for part in &parts {
+        db::mash_types::ensure_mash_type(&conn, &part.mash_type)?;
+    }
+    db::mashes::split_mash(&conn, &id, &parts)
+}
+
+#[tauri::command]
+pub fn get_mash_types(state: State<DbState>) -> Result<Vec<MashTypeDef>, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::mash_types::get_mash_types(&conn)
+}
+
+#[tauri::command]
+pub fn save_mash_type(
+    state: State<DbState>,
+    code: String,
+    labels: HashMap<String, String>,
+    description: String,
+    role: Option<String>,
+) -> Result<MashTypeDef, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::mash_types::save_mash_type(&conn, &code, &labels, &description, role.as_deref())
+}
+
+#[tauri::command]
+pub fn delete_mash_type(state: State<DbState>, code: String) -> Result<(), String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::mash_types::delete_mash_type(&conn, &code)
+}
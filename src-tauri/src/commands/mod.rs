@@ -1,9 +1,16 @@
+pub mod ask;
 pub mod auth;
+pub mod conflicts;
+pub mod digests;
 pub mod graph;
 pub mod mashes;
 pub mod pipeline;
+pub mod questions;
+pub mod reviews;
 pub mod search;
 pub mod settings;
+pub mod timeline;
+pub mod usage;
 
 use std::sync::{Arc, Mutex};
 
@@ -3,14 +3,14 @@ use tauri::State;
 use crate::ai::embedding::resolve_embedding_config;
 use crate::commands::DbState;
 use crate::db;
-use crate::models::PipelineStatus;
+use crate::models::{PipelineRunResult, PipelineStatus};
 use crate::pipeline::scheduler::PipelineSchedulerState;
 
 #[tauri::command]
 pub async fn trigger_pipeline(
     db_state: State<'_, DbState>,
     scheduler_state: State<'_, PipelineSchedulerState>,
-) -> Result<(), String> {
+) -> Result<PipelineRunResult, String> {
     let config = {
         let conn = db_state.0.lock().map_err(|e| e.to_string())?;
         resolve_embedding_config(&conn)?
@@ -67,6 +67,8 @@ pub fn get_pipeline_status(
         mashes.len() as u32
     };
 
+    let budget = crate::pipeline::budget::get_budget_status(&conn)?;
+
     let next_run = scheduler_state.next_run();
     let running = scheduler_state.is_running();
     let progress = scheduler_state.get_progress();
@@ -80,5 +82,7 @@ pub fn get_pipeline_status(
         on_still_count,
         running,
         progress,
+        budget,
+        last_run_paused: scheduler_state.last_result(),
     })
 }
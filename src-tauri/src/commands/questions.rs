@@ -0,0 +1,39 @@
+use tauri::State;
+
+use crate::analytics;
+use crate::commands::DbState;
+use crate::db;
+use crate::models::OpenQuestion;
+
+/// Unresolved questions and problems, oldest and most central first.
+#[tauri::command(async)]
+pub fn get_open_questions(
+    state: State<DbState>,
+    limit: Option<usize>,
+) -> Result<Vec<OpenQuestion>, String> {
+    // Centrality is computed without holding the lock.
+    let (mut questions, graph) = {
+        let conn = state.0.lock().map_err(|e| e.to_string())?;
+        (db::questions::get_open_questions(&conn)?, analytics::load_graph(&conn)?)
+    };
+    let pagerank = analytics::metrics::compute_pagerank(&graph);
+    db::questions::rank_open_questions(&mut questions, &pagerank);
+    questions.truncate(limit.unwrap_or(50));
+    Ok(questions)
+}
+
+#[tauri::command]
+pub fn resolve_question(
+    state: State<DbState>,
+    id: String,
+    resolved_by: Option<String>,
+) -> Result<(), String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::questions::resolve_question(&conn, &id, resolved_by.as_deref())
+}
+
+#[tauri::command]
+pub fn reopen_question(state: State<DbState>, id: String) -> Result<(), String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::questions::reopen_question(&conn, &id)
+}
@@ -0,0 +1,39 @@
+use tauri::State;
+
+use crate::analytics;
+use crate::commands::DbState;
+use crate::db;
+use crate::models::{DueReview, ReviewState};
+
+/// Mashes due for review. `prioritize` weights central and conflicting mashes
+/// higher; it defaults to the `review_prioritize` setting.
+#[tauri::command(async)]
+pub fn get_due_reviews(
+    state: State<DbState>,
+    limit: Option<usize>,
+    prioritize: Option<bool>,
+) -> Result<Vec<DueReview>, String> {
+    let now = db::now_ms();
+    let (mut reviews, graph, prioritize) = {
+        let conn = state.0.lock().map_err(|e| e.to_string())?;
+        let prioritize = match prioritize {
+            Some(p) => p,
+            None => db::settings::get_setting(&conn, "review_prioritize")?.as_deref() == Some("true"),
+        };
+        let graph = if prioritize { Some(analytics::load_graph(&conn)?) } else { None };
+        (db::reviews::get_due_reviews(&conn, now)?, graph, prioritize)
+    };
+    let pagerank = graph
+        .map(|g| analytics::metrics::compute_pagerank(&g))
+        .unwrap_or_default();
+    db::reviews::rank_due_reviews(&mut reviews, &pagerank, now, prioritize);
+    reviews.truncate(limit.unwrap_or(20));
+    Ok(reviews)
+}
+
+/// Grade a review from 0 (forgotten) to 5 (perfect recall).
+#[tauri::command]
+pub fn record_review(state: State<DbState>, id: String, grade: u8) -> Result<ReviewState, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::reviews::record_review(&conn, &id, grade)
+}
@@ -21,7 +21,7 @@ pub async fn search_semantic(
 ) -> Result<GraphData, String> {
     let (config, cached) = {
         let conn = state.0.lock().map_err(|e| e.to_string())?;
-        let config = resolve_embedding_config(&conn)?;
+        let config = resolve_embedding_config(&conn)?.tracked(&state.0, "search", None);
         let provider = config.provider.as_str().to_string();
         let cached = db::search_cache::find_cached_embedding(&conn, &query, &provider)?;
         (config, cached)
@@ -5,6 +5,7 @@ use crate::ai::embedding::{
 };
 use crate::commands::DbState;
 use crate::db;
+use crate::models::{DryRunReport, EmbeddingCacheStats};
 use crate::pipeline::scheduler::PipelineSchedulerState;
 
 #[tauri::command]
@@ -177,3 +178,44 @@ pub async fn reembed_all(
 
     Ok(reset_count)
 }
+
+/// What `reextract_relationships` would process, cost and delete; calls no provider.
+#[tauri::command(async)]
+pub fn dry_run_reextract_relationships(state: State<DbState>) -> Result<DryRunReport, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    let config = resolve_embedding_config(&conn)?;
+    crate::pipeline::dry_run::preview_reextract(&conn, &config)
+}
+
+/// What `reembed_all` would process, cost and delete; calls no provider.
+#[tauri::command(async)]
+pub fn dry_run_reembed_all(state: State<DbState>) -> Result<DryRunReport, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    let config = resolve_embedding_config(&conn)?;
+    crate::pipeline::dry_run::preview_reembed(&conn, &config)
+}
+
+#[tauri::command]
+pub fn get_embedding_cache_stats(state: State<DbState>) -> Result<EmbeddingCacheStats, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::embedding_cache::get_embedding_cache_stats(&conn)
+}
+
+/// Remove cached embeddings unused for `older_than_days` and trim the cache to
+/// `max_entries` (default: the configured limit). Returns the remaining size.
+#[tauri::command]
+pub fn prune_embedding_cache(
+    state: State<DbState>,
+    older_than_days: Option<i64>,
+    max_entries: Option<usize>,
+) -> Result<EmbeddingCacheStats, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    let unused_before = older_than_days.map(|d| db::now_ms() - d * 24 * 60 * 60 * 1000);
+    let max_entries = match max_entries {
+        Some(n) => n,
+        None => db::embedding_cache::max_entries(&conn)?,
+    };
+    let deleted = db::embedding_cache::prune_embedding_cache(&conn, unused_before, max_entries)?;
+    log::info!("Pruned {} cached embeddings", deleted);
+    db::embedding_cache::get_embedding_cache_stats(&conn)
+}
@@ -0,0 +1,28 @@
+use tauri::State;
+
+use crate::commands::DbState;
+use crate::db;
+use crate::models::{ActivityBucket, TimelinePage};
+
+#[tauri::command]
+pub fn get_activity_histogram(
+    state: State<DbState>,
+    from: i64,
+    to: i64,
+    bucket: Option<String>,
+) -> Result<Vec<ActivityBucket>, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::timeline::get_activity_histogram(&conn, from, to, bucket.as_deref().unwrap_or("day"))
+}
+
+#[tauri::command]
+pub fn get_timeline_feed(
+    state: State<DbState>,
+    from: i64,
+    to: i64,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<TimelinePage, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::timeline::get_timeline_feed(&conn, from, to, offset.unwrap_or(0), limit.unwrap_or(50))
+}
@@ -0,0 +1,12 @@
+use tauri::State;
+
+use crate::commands::DbState;
+use crate::db;
+use crate::models::UsageReport;
+
+/// Token usage and estimated cost between `from` and `to` (Unix ms).
+#[tauri::command]
+pub fn get_usage_report(state: State<DbState>, from: i64, to: i64) -> Result<UsageReport, String> {
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    db::usage::get_usage_report(&conn, from, to)
+}
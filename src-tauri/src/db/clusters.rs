@@ -0,0 +1,270 @@
+use std::collections::{HashMap, HashSet};
+
+use rusqlite::{params, Connection};
+
+use crate::db::now_ms;
+use crate::models::Cluster;
+
+/// A summary is regenerated once less than this share of members (Jaccard)
+/// is still the same as when it was written.
+const MIN_SUMMARY_OVERLAP: f64 = 0.8;
+
+/// A cluster whose cached summary is missing or out of date.
+pub struct StaleCluster {
+    pub id: i64,
+    pub member_ids: Vec<String>,
+    pub member_summaries: Vec<String>,
+}
+
+fn row_to_cluster(row: &rusqlite::Row) -> rusqlite::Result<Cluster> {
+    Ok(Cluster {
+        id: row.get(0)?,
+        size: row.get(1)?,
+        title: row.get(2)?,
+        summary: row.get(3)?,
+        summary_model: row.get(4)?,
+        summarized_at: row.get(5)?,
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
+
+pub fn get_clusters(conn: &Connection) -> Result<Vec<Cluster>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, size, title, summary, summary_model, summarized_at, created_at, updated_at
+             FROM clusters ORDER BY size DESC, id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], row_to_cluster)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+fn get_assignments(conn: &Connection) -> Result<HashMap<String, i64>, String> {
+    let mut stmt = conn
+        .prepare("SELECT mash_id, cluster_id FROM mash_clusters")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Replace the stored partition with `communities`.
+///
+/// Each community keeps the ID of the previous cluster it overlaps most, matched
+/// greedily from the largest overlap down, so topics keep their number across
+/// pipeline runs. Communities without a match get a fresh ID and clusters that
+/// no longer match anything are dropped.
+pub fn save_clusters(conn: &Connection, communities: &[Vec<String>]) -> Result<Vec<Cluster>, String> {
+    let previous = get_assignments(conn)?;
+
+    let mut overlaps: Vec<(usize, usize, i64)> = Vec::new();
+    for (index, members) in communities.iter().enumerate() {
+        let mut counts: HashMap<i64, usize> = HashMap::new();
+        for id in members {
+            if let Some(&cluster_id) = previous.get(id) {
+                *counts.entry(cluster_id).or_insert(0) += 1;
+            }
+        }
+        overlaps.extend(counts.into_iter().map(|(cluster_id, n)| (n, index, cluster_id)));
+    }
+    overlaps.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+
+    let mut assigned: Vec<Option<i64>> = vec![None; communities.len()];
+    let mut reused: HashSet<i64> = HashSet::new();
+    for (_, index, cluster_id) in overlaps {
+        if assigned[index].is_none() && !reused.contains(&cluster_id) {
+            assigned[index] = Some(cluster_id);
+            reused.insert(cluster_id);
+        }
+    }
+
+    let now = now_ms();
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM mash_clusters", [])
+        .map_err(|e| e.to_string())?;
+    let mut kept: Vec<i64> = Vec::with_capacity(communities.len());
+    for (members, cluster_id) in communities.iter().zip(assigned) {
+        let cluster_id = match cluster_id {
+            Some(id) => {
+                tx.execute(
+                    "UPDATE clusters SET size = ?1, updated_at = ?2 WHERE id = ?3",
+                    params![members.len() as i64, now, id],
+                )
+                .map_err(|e| e.to_string())?;
+                id
+            }
+            None => {
+                tx.execute(
+                    "INSERT INTO clusters (size, created_at, updated_at) VALUES (?1, ?2, ?3)",
+                    params![members.len() as i64, now, now],
+                )
+                .map_err(|e| e.to_string())?;
+                tx.last_insert_rowid()
+            }
+        };
+        for mash_id in members {
+            tx.execute(
+                "INSERT INTO mash_clusters (mash_id, cluster_id) VALUES (?1, ?2)",
+                params![mash_id, cluster_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        kept.push(cluster_id);
+    }
+    let kept_list = kept
+        .iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    tx.execute(
+        &format!("DELETE FROM clusters WHERE id NOT IN ({})", kept_list),
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    get_clusters(conn)
+}
+
+fn jaccard(a: &HashSet<&str>, b: &HashSet<&str>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// Clusters that have never been summarized or whose membership drifted too far
+/// from the set the summary was written for. Members come newest first.
+pub fn get_stale_clusters(conn: &Connection) -> Result<Vec<StaleCluster>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, summary_members FROM clusters WHERE size > 1 ORDER BY size DESC, id")
+        .map_err(|e| e.to_string())?;
+    let clusters: Vec<(i64, Option<String>)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut members_stmt = conn
+        .prepare(
+            "SELECT m.id, m.summary FROM mash_clusters mc
+             JOIN mashes m ON m.id = mc.mash_id
+             WHERE mc.cluster_id = ?1
+             ORDER BY m.updated_at DESC, m.id",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut stale = Vec::new();
+    for (id, summary_members) in clusters {
+        let members: Vec<(String, String)> = members_stmt
+            .query_map(params![id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        let previous: Option<Vec<String>> =
+            summary_members.and_then(|json| serde_json::from_str(&json).ok());
+        let is_stale = match previous {
+            None => true,
+            Some(previous) => {
+                let before: HashSet<&str> = previous.iter().map(|s| s.as_str()).collect();
+                let now: HashSet<&str> = members.iter().map(|(id, _)| id.as_str()).collect();
+                jaccard(&before, &now) < MIN_SUMMARY_OVERLAP
+            }
+        };
+        if is_stale {
+            let (member_ids, member_summaries) = members.into_iter().unzip();
+            stale.push(StaleCluster {
+                id,
+                member_ids,
+                member_summaries,
+            });
+        }
+    }
+    Ok(stale)
+}
+
+pub fn save_cluster_summary(
+    conn: &Connection,
+    id: i64,
+    title: &str,
+    summary: &str,
+    member_ids: &[String],
+    model: &str,
+) -> Result<(), String> {
+    let members_json = serde_json::to_string(member_ids).map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE clusters SET title = ?1, summary = ?2, summary_members = ?3,
+                             summary_model = ?4, summarized_at = ?5
+         WHERE id = ?6",
+        params![title.trim(), summary.trim(), members_json, model, now_ms(), id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_db(ids: &[&str]) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "foreign_keys", "ON").unwrap();
+        crate::db::create_schema(&conn);
+        crate::db::migrate(&conn);
+        for id in ids {
+            conn.execute(
+                "INSERT INTO mashes (id, type, status, summary, created_at, updated_at)
+                 VALUES (?1, 'insight', 'JARRED', ?1, 0, 0)",
+                params![id],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    fn members(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn id_of(conn: &Connection, mash_id: &str) -> i64 {
+        get_assignments(conn).unwrap()[mash_id]
+    }
+
+    #[test]
+    fn rerun_keeps_cluster_ids() {
+        let conn = test_db(&["a", "b", "c", "d", "e"]);
+        let communities = vec![members(&["a", "b", "c"]), members(&["d", "e"])];
+        save_clusters(&conn, &communities).unwrap();
+        let (first, second) = (id_of(&conn, "a"), id_of(&conn, "d"));
+
+        // The same partition in a different order keeps both IDs
+        save_clusters(&conn, &[communities[1].clone(), communities[0].clone()]).unwrap();
+        assert_eq!(id_of(&conn, "a"), first);
+        assert_eq!(id_of(&conn, "d"), second);
+
+        // A member moving across keeps each cluster on its majority
+        save_clusters(&conn, &[members(&["a", "b"]), members(&["c", "d", "e"])]).unwrap();
+        assert_eq!(id_of(&conn, "a"), first);
+        assert_eq!(id_of(&conn, "c"), second);
+    }
+
+    #[test]
+    fn unmatched_clusters_are_replaced() {
+        let conn = test_db(&["a", "b", "c", "d"]);
+        save_clusters(&conn, &[members(&["a", "b"])]).unwrap();
+        let old = id_of(&conn, "a");
+
+        let clusters = save_clusters(&conn, &[members(&["c", "d"])]).unwrap();
+        assert_eq!(clusters.len(), 1);
+        assert_ne!(clusters[0].id, old);
+        assert!(!get_assignments(&conn).unwrap().contains_key("a"));
+    }
+}
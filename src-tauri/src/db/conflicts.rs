@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+
+use rusqlite::{params, Connection};
+
+use crate::db::now_ms;
+use crate::models::{ConflictGroup, ConflictItem, ConflictResolution, ConflictsReport};
+
+/// One side of a conflict as shown to the model.
+pub struct ConflictSide {
+    pub id: String,
+    pub mash_type: String,
+    pub summary: String,
+    pub context: String,
+    pub created_at: i64,
+}
+
+pub struct ConflictForReview {
+    pub edge_id: i64,
+    pub source: ConflictSide,
+    pub target: ConflictSide,
+}
+
+/// Every CONFLICTS_WITH edge between JARRED mashes, grouped by the cluster both
+/// ends share. Groups with more conflicts come first and cross-topic conflicts
+/// last; within a group, the most confident and most recent lead.
+pub fn get_conflicts_report(conn: &Connection) -> Result<ConflictsReport, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT e.id, e.source_id, s.summary, s.type, e.target_id, t.summary, t.type,
+                    e.confidence, e.rationale, e.source, e.review_state, e.updated_at,
+                    cs.cluster_id, ct.cluster_id,
+                    r.edge_id, r.superseding_id, r.rationale, r.model, r.proposed_at,
+                    st.role IS 'decision' AND tt.role IS 'decision'
+             FROM edges e
+             JOIN mashes s ON s.id = e.source_id
+             JOIN mashes t ON t.id = e.target_id
+             LEFT JOIN mash_types st ON st.code = s.type
+             LEFT JOIN mash_types tt ON tt.code = t.type
+             LEFT JOIN mash_clusters cs ON cs.mash_id = e.source_id
+             LEFT JOIN mash_clusters ct ON ct.mash_id = e.target_id
+             LEFT JOIN conflict_resolutions r ON r.edge_id = e.id
+             WHERE e.relation_type = 'CONFLICTS_WITH'
+               AND s.status = 'JARRED' AND t.status = 'JARRED'
+             ORDER BY e.confidence DESC, e.updated_at DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows: Vec<(ConflictItem, Option<i64>)> = stmt
+        .query_map([], |row| {
+            let source_type: String = row.get(3)?;
+            let target_type: String = row.get(6)?;
+            let source_cluster: Option<i64> = row.get(12)?;
+            let target_cluster: Option<i64> = row.get(13)?;
+            let resolved: Option<i64> = row.get(14)?;
+            let resolution = match resolved {
+                Some(_) => Some(ConflictResolution {
+                    superseding_id: row.get(15)?,
+                    rationale: row.get(16)?,
+                    model: row.get(17)?,
+                    proposed_at: row.get(18)?,
+                }),
+                None => None,
+            };
+            let cluster = source_cluster.filter(|_| source_cluster == target_cluster);
+            Ok((
+                ConflictItem {
+                    edge_id: row.get(0)?,
+                    source_id: row.get(1)?,
+                    source_summary: row.get(2)?,
+                    decision_conflict: row.get(19)?,
+                    source_type,
+                    target_id: row.get(4)?,
+                    target_summary: row.get(5)?,
+                    target_type,
+                    confidence: row.get(7)?,
+                    rationale: row.get(8)?,
+                    edge_source: row.get(9)?,
+                    review_state: row.get(10)?,
+                    updated_at: row.get(11)?,
+                    resolution,
+                },
+                cluster,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare("SELECT id, title FROM clusters")
+        .map_err(|e| e.to_string())?;
+    let titles: HashMap<i64, Option<String>> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let total = rows.len() as u32;
+    let unresolved_decisions = rows
+        .iter()
+        .filter(|(item, _)| {
+            item.decision_conflict
+                && !matches!(&item.resolution, Some(r) if r.superseding_id.is_some())
+        })
+        .count() as u32;
+
+    let mut groups: Vec<ConflictGroup> = Vec::new();
+    for (item, cluster_id) in rows {
+        match groups.iter_mut().find(|g| g.cluster_id == cluster_id) {
+            Some(group) => group.conflicts.push(item),
+            None => groups.push(ConflictGroup {
+                cluster_id,
+                cluster_title: cluster_id.and_then(|id| titles.get(&id).cloned().flatten()),
+                conflicts: vec![item],
+            }),
+        }
+    }
+    groups.sort_by(|a, b| {
+        a.cluster_id
+            .is_none()
+            .cmp(&b.cluster_id.is_none())
+            .then(b.conflicts.len().cmp(&a.conflicts.len()))
+            .then(a.cluster_id.cmp(&b.cluster_id))
+    });
+
+    Ok(ConflictsReport {
+        groups,
+        total,
+        unresolved_decisions,
+    })
+}
+
+/// Conflicts the model has not judged yet, decision-vs-decision first.
+pub fn get_conflicts_for_review(conn: &Connection, limit: usize) -> Result<Vec<ConflictForReview>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT e.id,
+                    s.id, s.type, s.summary, s.context, s.created_at,
+                    t.id, t.type, t.summary, t.context, t.created_at
+             FROM edges e
+             JOIN mashes s ON s.id = e.source_id
+             JOIN mashes t ON t.id = e.target_id
+             LEFT JOIN mash_types st ON st.code = s.type
+             LEFT JOIN mash_types tt ON tt.code = t.type
+             WHERE e.relation_type = 'CONFLICTS_WITH'
+               AND s.status = 'JARRED' AND t.status = 'JARRED'
+               AND e.id NOT IN (SELECT edge_id FROM conflict_resolutions)
+             ORDER BY (st.role IS 'decision' AND tt.role IS 'decision') DESC,
+                      e.confidence DESC, e.updated_at DESC
+             LIMIT ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![limit as i64], |row| {
+            Ok(ConflictForReview {
+                edge_id: row.get(0)?,
+                source: ConflictSide {
+                    id: row.get(1)?,
+                    mash_type: row.get(2)?,
+                    summary: row.get(3)?,
+                    context: row.get(4)?,
+                    created_at: row.get(5)?,
+                },
+                target: ConflictSide {
+                    id: row.get(6)?,
+                    mash_type: row.get(7)?,
+                    summary: row.get(8)?,
+                    context: row.get(9)?,
+                    created_at: row.get(10)?,
+                },
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+pub fn save_conflict_resolution(
+    conn: &Connection,
+    edge_id: i64,
+    superseding_id: Option<&str>,
+    rationale: &str,
+    model: &str,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO conflict_resolutions (edge_id, superseding_id, rationale, model, proposed_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(edge_id) DO UPDATE SET
+            superseding_id = excluded.superseding_id,
+            rationale = excluded.rationale,
+            model = excluded.model,
+            proposed_at = excluded.proposed_at",
+        params![edge_id, superseding_id, rationale.trim(), model, now_ms()],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use rusqlite::{params, Connection};
+
+use crate::db::now_ms;
+use crate::models::{Conversation, ConversationMessage};
+
+fn row_to_message(row: &rusqlite::Row) -> rusqlite::Result<ConversationMessage> {
+    let cited: String = row.get(4)?;
+    Ok(ConversationMessage {
+        id: row.get(0)?,
+        conversation_id: row.get(1)?,
+        role: row.get(2)?,
+        content: row.get(3)?,
+        cited_ids: serde_json::from_str(&cited).unwrap_or_default(),
+        model: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+/// Conversations, most recently active first.
+pub fn get_conversations(conn: &Connection) -> Result<Vec<Conversation>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, title, created_at, updated_at FROM conversations
+             ORDER BY updated_at DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(Conversation {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                created_at: row.get(2)?,
+                updated_at: row.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Start a conversation titled after its first question.
+pub fn create_conversation(conn: &Connection, question: &str) -> Result<String, String> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let title: String = question.trim().chars().take(80).collect();
+    let now = now_ms();
+    conn.execute(
+        "INSERT INTO conversations (id, title, created_at, updated_at) VALUES (?1, ?2, ?3, ?3)",
+        params![id, title, now],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+pub fn conversation_exists(conn: &Connection, id: &str) -> Result<bool, String> {
+    conn.prepare("SELECT 1 FROM conversations WHERE id = ?1")
+        .and_then(|mut stmt| stmt.exists(params![id]))
+        .map_err(|e| e.to_string())
+}
+
+/// Store a question and its answer together, starting a conversation when
+/// `conversation_id` is None. Returns the conversation ID and the answer message.
+pub fn add_exchange(
+    conn: &Connection,
+    conversation_id: Option<String>,
+    question: &str,
+    answer: &str,
+    cited_ids: &[String],
+    model: &str,
+) -> Result<(String, ConversationMessage), String> {
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let conversation_id = match conversation_id {
+        Some(id) => id,
+        None => create_conversation(&tx, question)?,
+    };
+    add_message(&tx, &conversation_id, "user", question, &[], None)?;
+    let message = add_message(&tx, &conversation_id, "assistant", answer, cited_ids, Some(model))?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok((conversation_id, message))
+}
+
+pub fn delete_conversation(conn: &Connection, id: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM conversations WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Messages of a conversation in the order they were written.
+pub fn get_messages(conn: &Connection, conversation_id: &str) -> Result<Vec<ConversationMessage>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, conversation_id, role, content, cited_ids, model, created_at
+             FROM conversation_messages WHERE conversation_id = ?1
+             ORDER BY id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![conversation_id], row_to_message)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+pub fn add_message(
+    conn: &Connection,
+    conversation_id: &str,
+    role: &str,
+    content: &str,
+    cited_ids: &[String],
+    model: Option<&str>,
+) -> Result<ConversationMessage, String> {
+    let now = now_ms();
+    let cited = serde_json::to_string(cited_ids).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO conversation_messages (conversation_id, role, content, cited_ids, model, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![conversation_id, role, content, cited, model, now],
+    )
+    .map_err(|e| e.to_string())?;
+    let id = conn.last_insert_rowid();
+    conn.execute(
+        "UPDATE conversations SET updated_at = ?1 WHERE id = ?2",
+        params![now, conversation_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(ConversationMessage {
+        id,
+        conversation_id: conversation_id.to_string(),
+        role: role.to_string(),
+        content: content.to_string(),
+        cited_ids: cited_ids.to_vec(),
+        model: model.map(|m| m.to_string()),
+        created_at: now,
+    })
+}
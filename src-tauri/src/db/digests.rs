@@ -0,0 +1,187 @@
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::db::now_ms;
+use crate::models::Digest;
+
+/// A mash as it appears in a digest.
+pub struct DigestMash {
+    pub id: String,
+    pub mash_type: String,
+    pub summary: String,
+    pub context: String,
+}
+
+pub struct DigestConflict {
+    pub source: String,
+    pub target: String,
+    pub rationale: Option<String>,
+}
+
+/// Everything a digest reports on for one period.
+pub struct DigestInput {
+    pub period_start: String,
+    pub period_end: String,
+    pub new_mashes: Vec<DigestMash>,
+    /// The most central of `new_mashes`, most central first.
+    pub central: Vec<DigestMash>,
+    pub conflicts: Vec<DigestConflict>,
+    pub open_questions: Vec<DigestMash>,
+}
+
+fn row_to_digest_mash(row: &rusqlite::Row) -> rusqlite::Result<DigestMash> {
+    Ok(DigestMash {
+        id: row.get(0)?,
+        mash_type: row.get(1)?,
+        summary: row.get(2)?,
+        context: row.get(3)?,
+    })
+}
+
+fn row_to_digest(row: &rusqlite::Row) -> rusqlite::Result<Digest> {
+    Ok(Digest {
+        id: row.get(0)?,
+        period_start: row.get(1)?,
+        period_end: row.get(2)?,
+        markdown: row.get(3)?,
+        model: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+/// Local calendar date of a Unix millisecond timestamp, `YYYY-MM-DD`.
+pub fn format_date(conn: &Connection, ms: i64) -> Result<String, String> {
+    conn.query_row(
+        "SELECT date(?1 / 1000, 'unixepoch', 'localtime')",
+        params![ms],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Mashes that reached JARRED for the first time within [from, to) and are still there.
+pub fn get_newly_jarred(conn: &Connection, from: i64, to: i64) -> Result<Vec<DigestMash>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT m.id, m.type, m.summary, m.context
+             FROM mashes m
+             JOIN (SELECT mash_id, MIN(valid_from) AS jarred_at FROM mash_history
+                   WHERE status = 'JARRED' GROUP BY mash_id) h ON h.mash_id = m.id
+             WHERE m.status = 'JARRED' AND h.jarred_at >= ?1 AND h.jarred_at < ?2
+             ORDER BY h.jarred_at",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![from, to], row_to_digest_mash)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// CONFLICTS_WITH edges between JARRED mashes created within [from, to).
+pub fn get_new_conflicts(conn: &Connection, from: i64, to: i64) -> Result<Vec<DigestConflict>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT s.summary, t.summary, COALESCE(r.rationale, e.rationale)
+             FROM edges e
+             JOIN mashes s ON s.id = e.source_id
+             JOIN mashes t ON t.id = e.target_id
+             LEFT JOIN conflict_resolutions r ON r.edge_id = e.id
+             WHERE e.relation_type = 'CONFLICTS_WITH'
+               AND s.status = 'JARRED' AND t.status = 'JARRED'
+               AND e.created_at >= ?1 AND e.created_at < ?2
+             ORDER BY e.confidence DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![from, to], |row| {
+            Ok(DigestConflict {
+                source: row.get(0)?,
+                target: row.get(1)?,
+                rationale: row.get(2)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Unresolved JARRED questions that nothing supports yet, newest first.
+pub fn get_unsupported_questions(conn: &Connection, limit: usize) -> Result<Vec<DigestMash>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT m.id, m.type, m.summary, m.context
+             FROM mashes m
+             WHERE m.status = 'JARRED' AND m.type = 'question'
+               AND NOT EXISTS (SELECT 1 FROM question_resolutions r WHERE r.mash_id = m.id)
+               AND NOT EXISTS (
+                   SELECT 1 FROM edges e
+                   WHERE e.relation_type = 'SUPPORTS'
+                     AND (e.source_id = m.id OR e.target_id = m.id)
+               )
+             ORDER BY m.created_at DESC
+             LIMIT ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![limit as i64], row_to_digest_mash)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Digests, newest period first.
+pub fn get_digests(conn: &Connection) -> Result<Vec<Digest>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, period_start, period_end, markdown, model, created_at
+             FROM digests ORDER BY period_end DESC, id DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], row_to_digest)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+pub fn get_digest(conn: &Connection, id: i64) -> Result<Digest, String> {
+    conn.query_row(
+        "SELECT id, period_start, period_end, markdown, model, created_at
+         FROM digests WHERE id = ?1",
+        params![id],
+        row_to_digest,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Digest {} not found", id))
+}
+
+/// End of the most recent digest period, if any digest was written.
+pub fn get_last_period_end(conn: &Connection) -> Result<Option<i64>, String> {
+    conn.query_row("SELECT MAX(period_end) FROM digests", [], |row| row.get(0))
+        .map_err(|e| e.to_string())
+}
+
+pub fn save_digest(
+    conn: &Connection,
+    period_start: i64,
+    period_end: i64,
+    markdown: &str,
+    model: Option<&str>,
+) -> Result<Digest, String> {
+    let now = now_ms();
+    conn.execute(
+        "INSERT INTO digests (period_start, period_end, markdown, model, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![period_start, period_end, markdown, model, now],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(Digest {
+        id: conn.last_insert_rowid(),
+        period_start,
+        period_end,
+        markdown: markdown.to_string(),
+        model: model.map(|m| m.to_string()),
+        created_at: now,
+    })
+}
@@ -0,0 +1,253 @@
+use std::collections::{HashMap, HashSet};
+
+use rusqlite::{params, Connection};
+
+use crate::db::now_ms;
+use crate::models::DuplicateCandidate;
+use crate::similarity::{cosine_similarity, deserialize_embedding};
+
+/// Trigrams shared by more summaries than this ("the", "ing") are not counted
+/// pair by pair, which would make the candidate scan quadratic; see
+/// `find_duplicates_in` for how pairs that rely on them are still found.
+const MAX_TRIGRAM_POSTINGS: usize = 50;
+
+struct DuplicateSource {
+    id: String,
+    summary: String,
+    status: String,
+    embedding: Option<Vec<f32>>,
+    trigrams: HashSet<String>,
+}
+
+fn pair_key(a: usize, b: usize) -> (usize, usize) {
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+fn load_sources(conn: &Connection) -> Result<Vec<DuplicateSource>, String> {
+    let mut stmt = conn
+        .prepare("SELECT rowid, id, summary, status, embedding FROM mashes ORDER BY created_at")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            let blob: Option<Vec<u8>> = row.get(4)?;
+            Ok((
+                row.get::<_, i64>(0)?,
+                DuplicateSource {
+                    id: row.get(1)?,
+                    summary: row.get(2)?,
+                    status: row.get(3)?,
+                    embedding: blob.map(|b| deserialize_embedding(&b)),
+                    trigrams: HashSet::new(),
+                },
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    let mut by_rowid: HashMap<i64, usize> = HashMap::new();
+    let mut sources = Vec::new();
+    for row in rows {
+        let (rowid, source) = row.map_err(|e| e.to_string())?;
+        by_rowid.insert(rowid, sources.len());
+        sources.push(source);
+    }
+
+    // The trigram index already tokenizes every summary; read it back rather
+    // than re-tokenizing here.
+    let mut stmt = conn
+        .prepare("SELECT doc, term FROM mashes_fts_vocab WHERE col = 'summary'")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?;
+    for row in rows {
+        let (rowid, term) = row.map_err(|e| e.to_string())?;
+        if let Some(&i) = by_rowid.get(&rowid) {
+            sources[i].trigrams.insert(term);
+        }
+    }
+    Ok(sources)
+}
+
+fn get_dismissed_pairs(conn: &Connection) -> Result<HashSet<(String, String)>, String> {
+    let mut stmt = conn
+        .prepare("SELECT source_id, target_id FROM duplicate_dismissals")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<HashSet<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let shared = a.intersection(b).count();
+    let union = a.len() + b.len() - shared;
+    if union == 0 {
+        0.0
+    } else {
+        shared as f64 / union as f64
+    }
+}
+
+/// Everything the duplicate scan reads, loaded up front so the comparison can
+/// run without holding the database lock.
+pub struct DuplicateScan {
+    sources: Vec<DuplicateSource>,
+    dismissed: HashSet<(String, String)>,
+}
+
+pub fn load_duplicate_scan(conn: &Connection) -> Result<DuplicateScan, String> {
+    Ok(DuplicateScan {
+        sources: load_sources(conn)?,
+        dismissed: get_dismissed_pairs(conn)?,
+    })
+}
+
+/// Pairs of mashes that are probably the same idea captured twice: embeddings at
+/// or above `min_cosine`, or summaries whose trigram sets overlap by at least
+/// `min_trigram`. Dismissed pairs are skipped; the strongest matches come first.
+pub fn find_duplicates(
+    conn: &Connection,
+    min_cosine: f64,
+    min_trigram: f64,
+    limit: usize,
+) -> Result<Vec<DuplicateCandidate>, String> {
+    let scan = load_duplicate_scan(conn)?;
+    Ok(find_duplicates_in(&scan, min_cosine, min_trigram, limit))
+}
+
+pub fn find_duplicates_in(
+    scan: &DuplicateScan,
+    min_cosine: f64,
+    min_trigram: f64,
+    limit: usize,
+) -> Vec<DuplicateCandidate> {
+    let sources = &scan.sources;
+    let dismissed = &scan.dismissed;
+
+    let mut pairs: HashSet<(usize, usize)> = HashSet::new();
+
+    let embedded: Vec<usize> = (0..sources.len())
+        .filter(|&i| sources[i].embedding.is_some())
+        .collect();
+    for (n, &i) in embedded.iter().enumerate() {
+        let a = sources[i].embedding.as_deref().unwrap_or_default();
+        for &j in &embedded[n + 1..] {
+            let b = sources[j].embedding.as_deref().unwrap_or_default();
+            if cosine_similarity(a, b) as f64 >= min_cosine {
+                pairs.insert(pair_key(i, j));
+            }
+        }
+    }
+
+    let mut postings: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, source) in sources.iter().enumerate() {
+        for term in &source.trigrams {
+            postings.entry(term.as_str()).or_default().push(i);
+        }
+    }
+    // Trigrams each summary has that are too common to count pair by pair
+    let mut common = vec![0usize; sources.len()];
+    let mut shared: HashMap<(usize, usize), usize> = HashMap::new();
+    for docs in postings.values() {
+        if docs.len() > MAX_TRIGRAM_POSTINGS {
+            for &i in docs {
+                common[i] += 1;
+            }
+            continue;
+        }
+        for (n, &i) in docs.iter().enumerate() {
+            for &j in &docs[n + 1..] {
+                *shared.entry(pair_key(i, j)).or_default() += 1;
+            }
+        }
+    }
+    let could_match = |i: usize, j: usize, rare_shared: usize| {
+        // At most every common trigram of the sparser side is shared too
+        let most_shared = rare_shared + common[i].min(common[j]);
+        let fewest_union = sources[i].trigrams.len() + sources[j].trigrams.len() - most_shared;
+        fewest_union > 0 && most_shared as f64 / fewest_union as f64 >= min_trigram
+    };
+    for ((i, j), count) in shared {
+        if could_match(i, j, count) {
+            pairs.insert((i, j));
+        }
+    }
+    // Pairs sharing no rare trigram at all can only match when both summaries
+    // are mostly common trigrams; compare those few exhaustively.
+    let mostly_common: Vec<usize> = (0..sources.len())
+        .filter(|&i| {
+            !sources[i].trigrams.is_empty()
+                && common[i] as f64 >= min_trigram * sources[i].trigrams.len() as f64
+        })
+        .collect();
+    for (n, &i) in mostly_common.iter().enumerate() {
+        for &j in &mostly_common[n + 1..] {
+            if could_match(i, j, 0) {
+                pairs.insert(pair_key(i, j));
+            }
+        }
+    }
+
+    let mut candidates = Vec::new();
+    for (i, j) in pairs {
+        let (a, b) = (&sources[i], &sources[j]);
+        let key = if a.id <= b.id {
+            (a.id.clone(), b.id.clone())
+        } else {
+            (b.id.clone(), a.id.clone())
+        };
+        if dismissed.contains(&key) {
+            continue;
+        }
+        let cosine = match (&a.embedding, &b.embedding) {
+            (Some(ea), Some(eb)) => Some(cosine_similarity(ea, eb) as f64),
+            _ => None,
+        };
+        let trigram = jaccard(&a.trigrams, &b.trigrams);
+        if cosine.unwrap_or(0.0) < min_cosine && trigram < min_trigram {
+            continue;
+        }
+        // Sources were loaded oldest first, so the older mash is the source.
+        candidates.push(DuplicateCandidate {
+            source_id: a.id.clone(),
+            source_summary: a.summary.clone(),
+            source_status: a.status.clone(),
+            target_id: b.id.clone(),
+            target_summary: b.summary.clone(),
+            target_status: b.status.clone(),
+            cosine,
+            trigram,
+        });
+    }
+
+    candidates.sort_by(|x, y| {
+        let sx = x.cosine.unwrap_or(0.0).max(x.trigram);
+        let sy = y.cosine.unwrap_or(0.0).max(y.trigram);
+        sy.partial_cmp(&sx)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| x.source_id.cmp(&y.source_id))
+            .then_with(|| x.target_id.cmp(&y.target_id))
+    });
+    candidates.truncate(limit);
+    candidates
+}
+
+/// Remember that two mashes are distinct so the pair is not flagged again.
+pub fn dismiss_duplicate(conn: &Connection, source_id: &str, target_id: &str) -> Result<(), String> {
+    let (a, b) = if source_id <= target_id {
+        (source_id, target_id)
+    } else {
+        (target_id, source_id)
+    };
+    conn.execute(
+        "INSERT OR REPLACE INTO duplicate_dismissals (source_id, target_id, dismissed_at)
+         VALUES (?1, ?2, ?3)",
+        params![a, b, now_ms()],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
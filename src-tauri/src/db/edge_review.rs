@@ -0,0 +1,133 @@
+use std::collections::HashSet;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::db::now_ms;
+use crate::models::EdgeReviewItem;
+
+/// Rejections are remembered per unordered pair: once a person says two mashes
+/// are not related, the pipeline stops proposing any relation between them.
+fn pair_key<'a>(a: &'a str, b: &'a str) -> (&'a str, &'a str) {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// Pending AI edges between JARRED mashes, least confident first.
+pub fn get_pending_edges(conn: &Connection, limit: usize) -> Result<Vec<EdgeReviewItem>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT e.id, e.source_id, s.summary, e.target_id, t.summary, e.relation_type,
+                    e.confidence, e.rationale, e.model, e.extracted_at, e.evidence
+             FROM edges e
+             JOIN mashes s ON s.id = e.source_id
+             JOIN mashes t ON t.id = e.target_id
+             WHERE e.source = 'ai' AND e.review_state = 'pending'
+               AND s.status = 'JARRED' AND t.status = 'JARRED'
+             ORDER BY e.confidence ASC, e.updated_at DESC
+             LIMIT ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![limit as i64], |row| {
+            Ok(EdgeReviewItem {
+                id: row.get(0)?,
+                source_id: row.get(1)?,
+                source_summary: row.get(2)?,
+                target_id: row.get(3)?,
+                target_summary: row.get(4)?,
+                relation_type: row.get(5)?,
+                confidence: row.get(6)?,
+                rationale: row.get(7)?,
+                model: row.get(8)?,
+                extracted_at: row.get(9)?,
+                evidence: row.get(10)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+pub fn accept_edge(conn: &Connection, id: i64) -> Result<(), String> {
+    let updated = conn
+        .execute(
+            "UPDATE edges SET review_state = 'accepted', updated_at = ?1 WHERE id = ?2",
+            params![now_ms(), id],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Edge {} not found", id));
+    }
+    Ok(())
+}
+
+/// Delete the edge and remember the pair so it is never proposed again.
+pub fn reject_edge(conn: &Connection, id: i64) -> Result<(), String> {
+    let edge: Option<(String, String, String)> = conn
+        .query_row(
+            "SELECT source_id, target_id, relation_type FROM edges WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let (source_id, target_id, relation_type) =
+        edge.ok_or_else(|| format!("Edge {} not found", id))?;
+    let (a, b) = pair_key(&source_id, &target_id);
+
+    conn.execute(
+        "INSERT INTO edge_rejections (source_id, target_id, relation_type, rejected_at)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(source_id, target_id) DO UPDATE SET
+            relation_type = excluded.relation_type,
+            rejected_at = excluded.rejected_at",
+        params![a, b, relation_type, now_ms()],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM edges WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn is_rejected(conn: &Connection, source_id: &str, target_id: &str) -> Result<bool, String> {
+    let (a, b) = pair_key(source_id, target_id);
+    let found: Option<i64> = conn
+        .query_row(
+            "SELECT 1 FROM edge_rejections WHERE source_id = ?1 AND target_id = ?2",
+            params![a, b],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    Ok(found.is_some())
+}
+
+pub fn clear_rejection(conn: &Connection, source_id: &str, target_id: &str) -> Result<(), String> {
+    let (a, b) = pair_key(source_id, target_id);
+    conn.execute(
+        "DELETE FROM edge_rejections WHERE source_id = ?1 AND target_id = ?2",
+        params![a, b],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// All rejected pairs, each stored as (smaller id, larger id).
+pub fn get_rejected_pairs(conn: &Connection) -> Result<HashSet<(String, String)>, String> {
+    let mut stmt = conn
+        .prepare("SELECT source_id, target_id FROM edge_rejections")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<HashSet<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+pub fn is_pair_rejected(rejected: &HashSet<(String, String)>, a: &str, b: &str) -> bool {
+    let (a, b) = pair_key(a, b);
+    rejected.contains(&(a.to_string(), b.to_string()))
+}
@@ -1,7 +1,10 @@
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 
+use crate::ai::relationship::ExtractedRelation;
+use crate::db::edge_review::{clear_rejection, is_rejected};
 use crate::db::now_ms;
-use crate::models::{Edge, GraphData, GraphEdge, GraphFilters, GraphNode};
+use crate::db::relation_types::get_relation_type;
+use crate::models::{Edge, GraphData, GraphEdge, GraphFilters, GraphNode, RelationTypeDef};
 
 fn row_to_edge(row: &rusqlite::Row) -> rusqlite::Result<Edge> {
     Ok(Edge {
@@ -11,8 +14,13 @@ fn row_to_edge(row: &rusqlite::Row) -> rusqlite::Result<Edge> {
         relation_type: row.get(3)?,
         source: row.get(4)?,
         confidence: row.get(5)?,
-        created_at: row.get(6)?,
-        updated_at: row.get(7)?,
+        rationale: row.get(6)?,
+        model: row.get(7)?,
+        extracted_at: row.get(8)?,
+        review_state: row.get(9)?,
+        evidence: row.get(10)?,
+        created_at: row.get(11)?,
+        updated_at: row.get(12)?,
     })
 }
 
@@ -23,8 +31,9 @@ fn row_to_graph_node(row: &rusqlite::Row) -> rusqlite::Result<GraphNode> {
         summary: row.get(2)?,
         context: row.get(3)?,
         memo: row.get(4)?,
-        created_at: row.get(5)?,
-        updated_at: row.get(6)?,
+        cluster_id: row.get(5)?,
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
     })
 }
 
@@ -36,9 +45,43 @@ fn row_to_graph_edge(row: &rusqlite::Row) -> rusqlite::Result<GraphEdge> {
         relation_type: row.get(3)?,
         source: row.get(4)?,
         confidence: row.get(5)?,
+        rationale: row.get(6)?,
+        model: row.get(7)?,
+        extracted_at: row.get(8)?,
+        review_state: row.get(9)?,
+        evidence: row.get(10)?,
     })
 }
 
+/// Symmetric relations are stored once per pair, with the endpoints in
+/// lexicographic order, so A–B and B–A land on the same row.
+pub fn normalize_endpoints<'a>(
+    directed: bool,
+    source_id: &'a str,
+    target_id: &'a str,
+) -> (&'a str, &'a str) {
+    if !directed && source_id > target_id {
+        (target_id, source_id)
+    } else {
+        (source_id, target_id)
+    }
+}
+
+fn get_edge(conn: &Connection, id: i64) -> Result<Edge, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, source_id, target_id, relation_type, source, confidence,
+                    rationale, model, extracted_at, review_state, evidence, created_at, updated_at
+             FROM edges WHERE id = ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    stmt.query_row(params![id], row_to_edge)
+        .map_err(|e| e.to_string())
+}
+
+/// Create or overwrite the edge for (source, target, relation_type).
+/// Whoever writes last owns the row, so AI provenance is cleared, and a person
+/// linking the pair lifts any earlier rejection of it.
 pub fn add_edge(
     conn: &Connection,
     source_id: &str,
@@ -47,29 +90,94 @@ pub fn add_edge(
     source: &str,
     confidence: f64,
 ) -> Result<Edge, String> {
+    if source_id == target_id {
+        return Err("An edge cannot connect a mash to itself".to_string());
+    }
+    let def = get_relation_type(conn, relation_type)?;
+    let (source_id, target_id) = normalize_endpoints(def.directed, source_id, target_id);
+
     let now = now_ms();
     conn.execute(
-        "INSERT INTO edges (source_id, target_id, relation_type, source, confidence, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
-         ON CONFLICT(source_id, target_id) DO UPDATE SET
-            relation_type = excluded.relation_type,
+        "INSERT INTO edges (source_id, target_id, relation_type, source, confidence,
+                            review_state, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, 'accepted', ?6, ?7)
+         ON CONFLICT(source_id, target_id, relation_type) DO UPDATE SET
             source = excluded.source,
             confidence = excluded.confidence,
+            rationale = NULL,
+            model = NULL,
+            extracted_at = NULL,
+            evidence = NULL,
+            review_state = 'accepted',
             updated_at = excluded.updated_at",
         params![source_id, target_id, relation_type, source, confidence, now, now],
     )
     .map_err(|e| e.to_string())?;
+    clear_rejection(conn, source_id, target_id)?;
 
     let mut stmt = conn
         .prepare(
-            "SELECT id, source_id, target_id, relation_type, source, confidence, created_at, updated_at
-             FROM edges WHERE source_id = ?1 AND target_id = ?2",
+            "SELECT id, source_id, target_id, relation_type, source, confidence,
+                    rationale, model, extracted_at, review_state, evidence, created_at, updated_at
+             FROM edges WHERE source_id = ?1 AND target_id = ?2 AND relation_type = ?3",
         )
         .map_err(|e| e.to_string())?;
-    stmt.query_row(params![source_id, target_id], row_to_edge)
+    stmt.query_row(params![source_id, target_id, relation_type], row_to_edge)
         .map_err(|e| e.to_string())
 }
 
+/// Insert or refresh an AI-extracted edge as pending review. Rows owned by a person
+/// (any source other than 'ai'), accepted edges and rejected pairs are never
+/// touched; returns false when the write was skipped.
+pub fn upsert_ai_edge(
+    conn: &Connection,
+    relation_types: &[RelationTypeDef],
+    rel: &ExtractedRelation,
+    model: &str,
+) -> Result<bool, String> {
+    if rel.source_id == rel.target_id {
+        return Ok(false);
+    }
+    let directed = relation_types
+        .iter()
+        .find(|t| t.code == rel.relation)
+        .map(|t| t.directed)
+        .ok_or_else(|| format!("Unknown relation type: '{}'", rel.relation))?;
+    let (source_id, target_id) = normalize_endpoints(directed, &rel.source_id, &rel.target_id);
+    if is_rejected(conn, source_id, target_id)? {
+        return Ok(false);
+    }
+
+    let now = now_ms();
+    let changed = conn
+        .execute(
+            "INSERT INTO edges (source_id, target_id, relation_type, source, confidence, rationale,
+                                model, extracted_at, evidence, review_state, created_at, updated_at)
+             VALUES (?1, ?2, ?3, 'ai', ?4, ?5, ?6, ?7, ?8, 'pending', ?7, ?7)
+             ON CONFLICT(source_id, target_id, relation_type) DO UPDATE SET
+                confidence = excluded.confidence,
+                rationale = excluded.rationale,
+                model = excluded.model,
+                extracted_at = excluded.extracted_at,
+                evidence = excluded.evidence,
+                updated_at = excluded.updated_at
+             WHERE edges.source = 'ai' AND edges.review_state = 'pending'",
+            params![
+                source_id,
+                target_id,
+                rel.relation,
+                rel.confidence,
+                rel.rationale,
+                model,
+                now,
+                rel.evidence
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(changed > 0)
+}
+
+/// Edits come from a person, so an edited AI edge also counts as accepted.
 pub fn update_edge(
     conn: &Connection,
     id: i64,
@@ -79,39 +187,175 @@ pub fn update_edge(
     let now = now_ms();
 
     if let Some(rt) = relation_type {
-        conn.execute(
-            "UPDATE edges SET relation_type = ?1, updated_at = ?2 WHERE id = ?3",
-            params![rt, now, id],
-        )
-        .map_err(|e| e.to_string())?;
+        let current = get_edge(conn, id)?;
+        if current.relation_type != rt {
+            let def = get_relation_type(conn, rt)?;
+            let (source_id, target_id) =
+                normalize_endpoints(def.directed, &current.source_id, &current.target_id);
+            conn.execute(
+                "UPDATE edges SET source_id = ?1, target_id = ?2, relation_type = ?3,
+                                  review_state = 'accepted', updated_at = ?4
+                 WHERE id = ?5",
+                params![source_id, target_id, rt, now, id],
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::SqliteFailure(ref err, _)
+                    if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+                {
+                    format!("These mashes are already connected by {}", rt)
+                }
+                e => e.to_string(),
+            })?;
+        }
     }
     if let Some(c) = confidence {
         conn.execute(
-            "UPDATE edges SET confidence = ?1, updated_at = ?2 WHERE id = ?3",
+            "UPDATE edges SET confidence = ?1, review_state = 'accepted', updated_at = ?2
+             WHERE id = ?3",
             params![c, now, id],
         )
         .map_err(|e| e.to_string())?;
     }
 
+    get_edge(conn, id)
+}
+
+pub fn delete_edge(conn: &Connection, id: i64) -> Result<(), String> {
+    conn.execute("DELETE FROM edges WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Every edge touching a mash, each paired with the summary of its other end.
+pub fn get_mash_edges(conn: &Connection, id: &str) -> Result<Vec<(Edge, String)>, String> {
     let mut stmt = conn
         .prepare(
-            "SELECT id, source_id, target_id, relation_type, source, confidence, created_at, updated_at
-             FROM edges WHERE id = ?1",
+            "SELECT e.id, e.source_id, e.target_id, e.relation_type, e.source, e.confidence,
+                    e.rationale, e.model, e.extracted_at, e.review_state, e.evidence,
+                    e.created_at, e.updated_at, m.summary
+             FROM edges e
+             JOIN mashes m ON m.id = CASE WHEN e.source_id = ?1 THEN e.target_id ELSE e.source_id END
+             WHERE e.source_id = ?1 OR e.target_id = ?1
+             ORDER BY e.confidence DESC",
         )
         .map_err(|e| e.to_string())?;
-    stmt.query_row(params![id], row_to_edge)
+    let rows = stmt
+        .query_map(params![id], |row| Ok((row_to_edge(row)?, row.get(13)?)))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
         .map_err(|e| e.to_string())
 }
 
-pub fn delete_edge(conn: &Connection, id: i64) -> Result<(), String> {
-    conn.execute("DELETE FROM edges WHERE id = ?1", params![id])
+/// Which of two edges on the same key survives a merge: a person's edge beats an
+/// AI one, accepted beats pending, then the more confident wins.
+fn edge_rank(source: &str, review_state: &str, confidence: f64) -> (bool, bool, f64) {
+    (source != "ai", review_state == "accepted", confidence)
+}
+
+/// Move every edge of `from_id` onto `to_id`. Edges between the two become
+/// self-loops and are dropped; when a moved edge collides with one the survivor
+/// already has, the better of the two is kept. Rejections follow the mash too.
+/// Returns the number of edges moved.
+pub fn repoint_edges(conn: &Connection, from_id: &str, to_id: &str) -> Result<u32, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT e.id, e.source_id, e.target_id, e.relation_type, e.source, e.review_state,
+                    e.confidence, COALESCE(rt.directed, 0)
+             FROM edges e
+             LEFT JOIN relation_types rt ON rt.code = e.relation_type
+             WHERE e.source_id = ?1 OR e.target_id = ?1",
+        )
         .map_err(|e| e.to_string())?;
-    Ok(())
+    let edges = stmt
+        .query_map(params![from_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, f64>(6)?,
+                row.get::<_, bool>(7)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let now = now_ms();
+    let mut moved = 0u32;
+    for (id, source_id, target_id, relation_type, source, review_state, confidence, directed) in
+        edges
+    {
+        let swap = |endpoint: &str| {
+            if endpoint == from_id {
+                to_id.to_string()
+            } else {
+                endpoint.to_string()
+            }
+        };
+        let (new_source, new_target) = (swap(&source_id), swap(&target_id));
+        if new_source == new_target {
+            delete_edge(conn, id)?;
+            continue;
+        }
+        let (new_source, new_target) = normalize_endpoints(directed, &new_source, &new_target);
+
+        let existing: Option<(i64, String, String, f64)> = conn
+            .query_row(
+                "SELECT id, source, review_state, confidence FROM edges
+                 WHERE source_id = ?1 AND target_id = ?2 AND relation_type = ?3 AND id != ?4",
+                params![new_source, new_target, relation_type, id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        if let Some((other_id, other_source, other_state, other_confidence)) = existing {
+            if edge_rank(&other_source, &other_state, other_confidence)
+                >= edge_rank(&source, &review_state, confidence)
+            {
+                delete_edge(conn, id)?;
+                continue;
+            }
+            delete_edge(conn, other_id)?;
+        }
+        conn.execute(
+            "UPDATE edges SET source_id = ?1, target_id = ?2, updated_at = ?3 WHERE id = ?4",
+            params![new_source, new_target, now, id],
+        )
+        .map_err(|e| e.to_string())?;
+        moved += 1;
+    }
+
+    conn.execute(
+        "INSERT OR IGNORE INTO edge_rejections (source_id, target_id, relation_type, rejected_at)
+         SELECT MIN(?2, other), MAX(?2, other), relation_type, rejected_at
+         FROM (
+             SELECT CASE WHEN source_id = ?1 THEN target_id ELSE source_id END AS other,
+                    relation_type, rejected_at
+             FROM edge_rejections
+             WHERE source_id = ?1 OR target_id = ?1
+         )
+         WHERE other != ?2",
+        params![from_id, to_id],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE conflict_resolutions SET superseding_id = ?2 WHERE superseding_id = ?1",
+        params![from_id, to_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(moved)
 }
 
+/// Delete AI edges that nobody has reviewed yet; accepted edges are kept.
 pub fn delete_ai_edges(conn: &Connection) -> Result<u32, String> {
     let count = conn
-        .execute("DELETE FROM edges WHERE source = 'ai'", [])
+        .execute(
+            "DELETE FROM edges WHERE source = 'ai' AND review_state = 'pending'",
+            [],
+        )
         .map_err(|e| e.to_string())?;
     Ok(count as u32)
 }
@@ -125,10 +369,20 @@ pub fn get_graph(conn: &Connection, filters: &GraphFilters) -> Result<GraphData,
             node_conditions.push(format!("m.type IN ({})", placeholders.join(",")));
         }
     }
+    if let Some(ref clusters) = filters.clusters {
+        if !clusters.is_empty() {
+            let ids: Vec<String> = clusters.iter().map(|c| c.to_string()).collect();
+            node_conditions.push(format!(
+                "m.id IN (SELECT mash_id FROM mash_clusters WHERE cluster_id IN ({}))",
+                ids.join(",")
+            ));
+        }
+    }
     let node_where = node_conditions.join(" AND ");
 
     let node_sql = format!(
-        "SELECT m.id, m.type, m.summary, m.context, m.memo, m.created_at, m.updated_at
+        "SELECT m.id, m.type, m.summary, m.context, m.memo,
+                (SELECT cluster_id FROM mash_clusters WHERE mash_id = m.id), m.created_at, m.updated_at
          FROM mashes m WHERE {}",
         node_where
     );
@@ -168,7 +422,8 @@ pub fn get_graph(conn: &Connection, filters: &GraphFilters) -> Result<GraphData,
     let edge_where = edge_conditions.join(" AND ");
 
     let edge_sql = format!(
-        "SELECT e.id, e.source_id, e.target_id, e.relation_type, e.source, e.confidence
+        "SELECT e.id, e.source_id, e.target_id, e.relation_type, e.source, e.confidence,
+                e.rationale, e.model, e.extracted_at, e.review_state, e.evidence
          FROM edges e WHERE {}",
         edge_where
     );
@@ -186,7 +441,8 @@ pub fn get_graph(conn: &Connection, filters: &GraphFilters) -> Result<GraphData,
 pub fn get_node_neighbors(conn: &Connection, id: &str) -> Result<GraphData, String> {
     let mut stmt = conn
         .prepare(
-            "SELECT m.id, m.type, m.summary, m.context, m.memo, m.created_at, m.updated_at
+            "SELECT m.id, m.type, m.summary, m.context, m.memo,
+                    (SELECT cluster_id FROM mash_clusters WHERE mash_id = m.id), m.created_at, m.updated_at
              FROM mashes m
              WHERE m.status = 'JARRED' AND (
                  m.id = ?1
@@ -203,7 +459,8 @@ pub fn get_node_neighbors(conn: &Connection, id: &str) -> Result<GraphData, Stri
 
     let mut stmt = conn
         .prepare(
-            "SELECT e.id, e.source_id, e.target_id, e.relation_type, e.source, e.confidence
+            "SELECT e.id, e.source_id, e.target_id, e.relation_type, e.source, e.confidence,
+                    e.rationale, e.model, e.extracted_at, e.review_state, e.evidence
              FROM edges e
              WHERE e.source_id = ?1 OR e.target_id = ?1",
         )
@@ -227,7 +484,8 @@ pub fn expand_node(conn: &Connection, id: &str, depth: u32) -> Result<GraphData,
                 FROM edges e JOIN reachable r ON (e.source_id = r.id OR e.target_id = r.id)
                 WHERE r.depth < ?2
              )
-             SELECT DISTINCT m.id, m.type, m.summary, m.context, m.memo, m.created_at, m.updated_at
+             SELECT DISTINCT m.id, m.type, m.summary, m.context, m.memo,
+                    (SELECT cluster_id FROM mash_clusters WHERE mash_id = m.id), m.created_at, m.updated_at
              FROM mashes m JOIN reachable r ON m.id = r.id
              WHERE m.status = 'JARRED'",
         )
@@ -249,7 +507,8 @@ pub fn expand_node(conn: &Connection, id: &str, depth: u32) -> Result<GraphData,
     let placeholders: Vec<String> = node_ids.iter().map(|id| format!("'{}'", id.replace('\'', "''"))).collect();
     let ids_str = placeholders.join(",");
     let edge_sql = format!(
-        "SELECT e.id, e.source_id, e.target_id, e.relation_type, e.source, e.confidence
+        "SELECT e.id, e.source_id, e.target_id, e.relation_type, e.source, e.confidence,
+                e.rationale, e.model, e.extracted_at, e.review_state, e.evidence
          FROM edges e
          WHERE e.source_id IN ({ids}) AND e.target_id IN ({ids})",
         ids = ids_str
@@ -264,3 +523,43 @@ pub fn expand_node(conn: &Connection, id: &str, depth: u32) -> Result<GraphData,
 
     Ok(GraphData { nodes, edges })
 }
+
+/// Fetch the given nodes and edges, e.g. everything along a set of paths.
+pub fn get_subgraph(conn: &Connection, node_ids: &[String], edge_ids: &[i64]) -> Result<GraphData, String> {
+    if node_ids.is_empty() {
+        return Ok(GraphData {
+            nodes: vec![],
+            edges: vec![],
+        });
+    }
+
+    let placeholders: Vec<String> = node_ids.iter().map(|id| format!("'{}'", id.replace('\'', "''"))).collect();
+    let node_sql = format!(
+        "SELECT m.id, m.type, m.summary, m.context, m.memo,
+                (SELECT cluster_id FROM mash_clusters WHERE mash_id = m.id), m.created_at, m.updated_at
+         FROM mashes m WHERE m.id IN ({})",
+        placeholders.join(",")
+    );
+    let mut stmt = conn.prepare(&node_sql).map_err(|e| e.to_string())?;
+    let nodes: Vec<GraphNode> = stmt
+        .query_map([], row_to_graph_node)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let ids: Vec<String> = edge_ids.iter().map(|id| id.to_string()).collect();
+    let edge_sql = format!(
+        "SELECT e.id, e.source_id, e.target_id, e.relation_type, e.source, e.confidence,
+                e.rationale, e.model, e.extracted_at, e.review_state, e.evidence
+         FROM edges e WHERE e.id IN ({})",
+        ids.join(",")
+    );
+    let mut stmt = conn.prepare(&edge_sql).map_err(|e| e.to_string())?;
+    let edges: Vec<GraphEdge> = stmt
+        .query_map([], row_to_graph_edge)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(GraphData { nodes, edges })
+}
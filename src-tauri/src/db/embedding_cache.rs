@@ -0,0 +1,156 @@
+use std::collections::{HashMap, HashSet};
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::ai::embedding::{requested_dimensions, EmbeddingConfig};
+use crate::db::now_ms;
+use crate::models::EmbeddingCacheStats;
+use crate::similarity::{deserialize_embedding, serialize_embedding};
+
+const DEFAULT_MAX_ENTRIES: usize = 20_000;
+
+/// Entry limit from the `embedding_cache_max_entries` setting.
+pub fn max_entries(conn: &Connection) -> Result<usize, String> {
+    Ok(crate::db::settings::get_setting(conn, "embedding_cache_max_entries")?
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MAX_ENTRIES))
+}
+
+/// Cached document embeddings for `config`'s provider, model and dimensions,
+/// keyed by text hash. Hits are marked as used.
+pub fn get_cached_embeddings(
+    conn: &Connection,
+    config: &EmbeddingConfig,
+    hashes: &[String],
+) -> Result<HashMap<String, Vec<f32>>, String> {
+    let provider = config.provider.as_str();
+    let dimensions = requested_dimensions(&config.provider);
+    let now = now_ms();
+    let mut select = conn
+        .prepare(
+            "SELECT embedding FROM embedding_cache
+             WHERE provider = ?1 AND model = ?2 AND dimensions = ?3 AND text_hash = ?4",
+        )
+        .map_err(|e| e.to_string())?;
+    let mut touch = conn
+        .prepare(
+            "UPDATE embedding_cache SET last_used_at = ?1
+             WHERE provider = ?2 AND model = ?3 AND dimensions = ?4 AND text_hash = ?5",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut found = HashMap::new();
+    for hash in hashes {
+        if found.contains_key(hash) {
+            continue;
+        }
+        let blob: Option<Vec<u8>> = select
+            .query_row(params![provider, config.embedding_model, dimensions, hash], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?;
+        if let Some(blob) = blob {
+            touch
+                .execute(params![now, provider, config.embedding_model, dimensions, hash])
+                .map_err(|e| e.to_string())?;
+            found.insert(hash.clone(), deserialize_embedding(&blob));
+        }
+    }
+    Ok(found)
+}
+
+/// Which of `hashes` are cached for `config`, without marking them as used.
+pub fn cached_hashes(
+    conn: &Connection,
+    config: &EmbeddingConfig,
+    hashes: &[String],
+) -> Result<HashSet<String>, String> {
+    let provider = config.provider.as_str();
+    let dimensions = requested_dimensions(&config.provider);
+    let mut stmt = conn
+        .prepare(
+            "SELECT 1 FROM embedding_cache
+             WHERE provider = ?1 AND model = ?2 AND dimensions = ?3 AND text_hash = ?4",
+        )
+        .map_err(|e| e.to_string())?;
+    let mut found = HashSet::new();
+    for hash in hashes {
+        if found.contains(hash) {
+            continue;
+        }
+        let hit = stmt
+            .exists(params![provider, config.embedding_model, dimensions, hash])
+            .map_err(|e| e.to_string())?;
+        if hit {
+            found.insert(hash.clone());
+        }
+    }
+    Ok(found)
+}
+
+/// Store fresh embeddings, then evict least recently used entries over the limit.
+pub fn save_cached_embeddings(
+    conn: &Connection,
+    config: &EmbeddingConfig,
+    entries: &[(String, Vec<f32>)],
+) -> Result<(), String> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let provider = config.provider.as_str();
+    let dimensions = requested_dimensions(&config.provider);
+    let now = now_ms();
+    for (hash, embedding) in entries {
+        conn.execute(
+            "INSERT OR REPLACE INTO embedding_cache
+                (provider, model, dimensions, text_hash, embedding, created_at, last_used_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)",
+            params![provider, config.embedding_model, dimensions, hash, serialize_embedding(embedding), now],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    evict_over(conn, max_entries(conn)?)?;
+    Ok(())
+}
+
+fn evict_over(conn: &Connection, max_entries: usize) -> Result<u32, String> {
+    let deleted = conn
+        .execute(
+            "DELETE FROM embedding_cache WHERE rowid NOT IN (
+                SELECT rowid FROM embedding_cache ORDER BY last_used_at DESC LIMIT ?1
+            )",
+            params![max_entries as i64],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(deleted as u32)
+}
+
+/// Drop entries unused since `unused_before` (Unix ms), then trim to
+/// `max_entries`. Returns how many entries were removed.
+pub fn prune_embedding_cache(
+    conn: &Connection,
+    unused_before: Option<i64>,
+    max_entries: usize,
+) -> Result<u32, String> {
+    let mut deleted = 0u32;
+    if let Some(before) = unused_before {
+        deleted += conn
+            .execute("DELETE FROM embedding_cache WHERE last_used_at < ?1", params![before])
+            .map_err(|e| e.to_string())? as u32;
+    }
+    deleted += evict_over(conn, max_entries)?;
+    Ok(deleted)
+}
+
+pub fn get_embedding_cache_stats(conn: &Connection) -> Result<EmbeddingCacheStats, String> {
+    conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(LENGTH(embedding)), 0) FROM embedding_cache",
+        [],
+        |row| {
+            Ok(EmbeddingCacheStats {
+                entries: row.get(0)?,
+                bytes: row.get(1)?,
+            })
+        },
+    )
+    .map_err(|e| e.to_string())
+}
@@ -0,0 +1,158 @@
+use std::collections::{HashMap, HashSet};
+
+use rusqlite::{params, Connection};
+
+use crate::models::{GraphData, GraphDiff, GraphEdge, GraphNode, RetypedEdge};
+
+/// The graph as `get_graph` would have shown it at `timestamp`: JARRED mashes
+/// and the edges between them. Cluster membership is not versioned, so
+/// historical nodes carry none; provenance comes from the edge's current row
+/// while it still exists.
+pub fn get_graph_at(conn: &Connection, timestamp: i64) -> Result<GraphData, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT h.mash_id, h.type, h.summary, h.context, h.memo,
+                    (SELECT MIN(valid_from) FROM mash_history WHERE mash_id = h.mash_id),
+                    h.valid_from
+             FROM mash_history h
+             WHERE h.valid_from <= ?1 AND (h.valid_to IS NULL OR h.valid_to > ?1)
+               AND h.status = 'JARRED'
+             ORDER BY h.mash_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let nodes = stmt
+        .query_map(params![timestamp], |row| {
+            Ok(GraphNode {
+                id: row.get(0)?,
+                node_type: row.get(1)?,
+                summary: row.get(2)?,
+                context: row.get(3)?,
+                memo: row.get(4)?,
+                cluster_id: None,
+                created_at: row.get(5)?,
+                updated_at: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    let node_ids: HashSet<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT h.edge_id, h.source_id, h.target_id, h.relation_type, h.source, h.confidence,
+                    e.rationale, e.model, e.extracted_at, h.review_state, e.evidence
+             FROM edge_history h
+             LEFT JOIN edges e ON e.id = h.edge_id
+             WHERE h.valid_from <= ?1 AND (h.valid_to IS NULL OR h.valid_to > ?1)
+             ORDER BY h.edge_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let edges = stmt
+        .query_map(params![timestamp], |row| {
+            Ok(GraphEdge {
+                id: row.get(0)?,
+                source_id: row.get(1)?,
+                target_id: row.get(2)?,
+                relation_type: row.get(3)?,
+                source: row.get(4)?,
+                confidence: row.get(5)?,
+                rationale: row.get(6)?,
+                model: row.get(7)?,
+                extracted_at: row.get(8)?,
+                review_state: row.get(9)?,
+                evidence: row.get(10)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|e| {
+            node_ids.contains(e.source_id.as_str()) && node_ids.contains(e.target_id.as_str())
+        })
+        .collect();
+
+    Ok(GraphData { nodes, edges })
+}
+
+fn edge_key(edge: &GraphEdge) -> (&str, &str, &str) {
+    (&edge.source_id, &edge.target_id, &edge.relation_type)
+}
+
+fn pair_key(edge: &GraphEdge) -> (&str, &str) {
+    if edge.source_id <= edge.target_id {
+        (&edge.source_id, &edge.target_id)
+    } else {
+        (&edge.target_id, &edge.source_id)
+    }
+}
+
+/// Nodes and edges added or removed between `from` and `to`. Edges are compared
+/// by endpoints and relation rather than row ID, so an edge that was deleted and
+/// re-extracted unchanged does not show up; a pair that lost one relation and
+/// gained another in between counts as retyped.
+pub fn diff_graph(conn: &Connection, from: i64, to: i64) -> Result<GraphDiff, String> {
+    let before = get_graph_at(conn, from)?;
+    let after = get_graph_at(conn, to)?;
+
+    let before_ids: HashSet<&str> = before.nodes.iter().map(|n| n.id.as_str()).collect();
+    let after_ids: HashSet<&str> = after.nodes.iter().map(|n| n.id.as_str()).collect();
+    let added_nodes = after
+        .nodes
+        .iter()
+        .filter(|n| !before_ids.contains(n.id.as_str()))
+        .cloned()
+        .collect();
+    let removed_nodes = before
+        .nodes
+        .iter()
+        .filter(|n| !after_ids.contains(n.id.as_str()))
+        .cloned()
+        .collect();
+
+    let before_keys: HashSet<_> = before.edges.iter().map(edge_key).collect();
+    let after_keys: HashSet<_> = after.edges.iter().map(edge_key).collect();
+    let mut added: Vec<&GraphEdge> = after
+        .edges
+        .iter()
+        .filter(|e| !before_keys.contains(&edge_key(e)))
+        .collect();
+    let removed: Vec<&GraphEdge> = before
+        .edges
+        .iter()
+        .filter(|e| !after_keys.contains(&edge_key(e)))
+        .collect();
+
+    let mut removed_by_pair: HashMap<(&str, &str), usize> = HashMap::new();
+    for (i, edge) in removed.iter().enumerate() {
+        removed_by_pair.entry(pair_key(edge)).or_insert(i);
+    }
+    let mut retyped_edges = Vec::new();
+    let mut matched: HashSet<usize> = HashSet::new();
+    added.retain(|edge| match removed_by_pair.remove(&pair_key(edge)) {
+        Some(i) => {
+            matched.insert(i);
+            retyped_edges.push(RetypedEdge {
+                edge: (*edge).clone(),
+                previous_relation_type: removed[i].relation_type.clone(),
+            });
+            false
+        }
+        None => true,
+    });
+    let removed_edges = removed
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !matched.contains(i))
+        .map(|(_, edge)| (*edge).clone())
+        .collect();
+
+    Ok(GraphDiff {
+        added_nodes,
+        removed_nodes,
+        added_edges: added.into_iter().cloned().collect(),
+        removed_edges,
+        retyped_edges,
+    })
+}
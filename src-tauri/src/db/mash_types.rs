@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::db::now_ms;
+use crate::models::MashTypeDef;
+
+/// Built-in taxonomy seeded on first run: (code, legacy Korean name, English label, description).
+/// The legacy name doubles as the Korean label.
+pub const BUILTIN_TYPES: &[(&str, &str, &str, &str)] = &[
+    ("decision", "결정", "Decision", "a decision that has been made"),
+    ("problem", "문제", "Problem", "a problem or issue to solve"),
+    ("insight", "인사이트", "Insight", "an insight or discovery"),
+    ("question", "질문", "Question", "a question needing discussion"),
+];
+
+/// What a type means to features that depend on it: `open` types (questions,
+/// problems) can be resolved, `decision` and `answer` types can resolve them,
+/// and conflicts between two `decision` types are surfaced first.
+pub const MASH_TYPE_ROLES: &[&str] = &["open", "decision", "answer"];
+
+/// Roles of the built-in types: (code, role).
+pub const BUILTIN_ROLES: &[(&str, &str)] = &[
+    ("decision", "decision"),
+    ("problem", "open"),
+    ("insight", "answer"),
+    ("question", "open"),
+];
+
+fn row_to_mash_type(row: &rusqlite::Row) -> rusqlite::Result<MashTypeDef> {
+    let labels_json: String = row.get(1)?;
+    Ok(MashTypeDef {
+        code: row.get(0)?,
+        labels: serde_json::from_str(&labels_json).unwrap_or_default(),
+        description: row.get(2)?,
+        position: row.get(3)?,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+        role: row.get(6)?,
+    })
+}
+
+/// Codes are stored on every mash, so keep them short and language-neutral.
+fn validate_code(code: &str) -> Result<(), String> {
+    let valid = !code.is_empty()
+        && code.len() <= 32
+        && code
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid mash type code '{}': use 1-32 lowercase letters, digits or '_'",
+            code
+        ))
+    }
+}
+
+pub fn get_mash_types(conn: &Connection) -> Result<Vec<MashTypeDef>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT code, labels, description, position, created_at, updated_at, role
+             FROM mash_types ORDER BY position, code",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], row_to_mash_type)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+pub fn ensure_mash_type(conn: &Connection, code: &str) -> Result<(), String> {
+    let exists: Option<i64> = conn
+        .query_row(
+            "SELECT 1 FROM mash_types WHERE code = ?1",
+            params![code],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    match exists {
+        Some(_) => Ok(()),
+        None => Err(format!("Unknown mash type: '{}'", code)),
+    }
+}
+
+/// Insert a new type or update the labels/description/role of an existing one.
+/// New types are appended after the current last position.
+pub fn save_mash_type(
+    conn: &Connection,
+    code: &str,
+    labels: &HashMap<String, String>,
+    description: &str,
+    role: Option<&str>,
+) -> Result<MashTypeDef, String> {
+    validate_code(code)?;
+    if description.trim().is_empty() {
+        return Err("Mash type description is required".to_string());
+    }
+    if let Some(role) = role.filter(|r| !MASH_TYPE_ROLES.contains(r)) {
+        return Err(format!(
+            "Invalid mash type role '{}': use one of {}",
+            role,
+            MASH_TYPE_ROLES.join(", ")
+        ));
+    }
+
+    let labels_json = serde_json::to_string(labels).map_err(|e| e.to_string())?;
+    let now = now_ms();
+    conn.execute(
+        "INSERT INTO mash_types (code, labels, description, position, created_at, updated_at, role)
+         VALUES (?1, ?2, ?3, (SELECT COALESCE(MAX(position), -1) + 1 FROM mash_types), ?4, ?5, ?6)
+         ON CONFLICT(code) DO UPDATE SET
+            labels = excluded.labels,
+            description = excluded.description,
+            role = excluded.role,
+            updated_at = excluded.updated_at",
+        params![code, labels_json, description.trim(), now, now, role],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT code, labels, description, position, created_at, updated_at, role
+             FROM mash_types WHERE code = ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    stmt.query_row(params![code], row_to_mash_type)
+        .map_err(|e| e.to_string())
+}
+
+/// Delete a type. Refuses while any mash still uses it.
+pub fn delete_mash_type(conn: &Connection, code: &str) -> Result<(), String> {
+    let in_use: u32 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM mashes WHERE type = ?1",
+            params![code],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if in_use > 0 {
+        return Err(format!(
+            "Mash type '{}' is used by {} mashes; reassign them first",
+            code, in_use
+        ));
+    }
+
+    conn.execute("DELETE FROM mash_types WHERE code = ?1", params![code])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// The role of type `code`, None when it has none or does not exist.
+pub fn get_role(conn: &Connection, code: &str) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT role FROM mash_types WHERE code = ?1",
+        params![code],
+        |row| row.get(0),
+    )
+    .optional()
+    .map(Option::flatten)
+    .map_err(|e| e.to_string())
+}
@@ -1,7 +1,8 @@
 use rusqlite::{params, Connection};
 
 use crate::db::now_ms;
-use crate::models::Mash;
+use crate::models::{Mash, MashRevision, SplitPart};
+use crate::similarity::content_hash;
 
 fn row_to_mash(row: &rusqlite::Row) -> rusqlite::Result<Mash> {
     Ok(Mash {
@@ -149,6 +150,7 @@ pub fn update_mash(
 
     conn.execute(&sql, param_refs.as_slice())
         .map_err(|e| e.to_string())?;
+    requeue_if_stale(conn, id)?;
 
     let mut stmt = conn
         .prepare(
@@ -161,10 +163,63 @@ pub fn update_mash(
         .map_err(|e| e.to_string())
 }
 
+/// Compare a mash's text with the text its embedding was computed from. A
+/// changed JARRED or RE_EXTRACT mash goes to RE_EMBED (and from there to
+/// RE_EXTRACT), a changed DISTILLED one back to ON_STILL. Restoring the text
+/// before the pipeline ran undoes that, returning the mash to the status it
+/// left, so a net-zero edit costs no API calls and drops no queued work.
+/// The old embedding is kept until the new one replaces it.
+fn requeue_if_stale(conn: &Connection, id: &str) -> Result<(), String> {
+    let (status, summary, context, memo, stored_hash, has_embedding, requeued_from): (
+        String,
+        String,
+        String,
+        String,
+        Option<String>,
+        bool,
+        Option<String>,
+    ) = conn
+        .query_row(
+            "SELECT status, summary, context, memo, content_hash, embedding IS NOT NULL, requeued_from
+             FROM mashes WHERE id = ?1",
+            params![id],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                    row.get(6)?,
+                ))
+            },
+        )
+        .map_err(|e| e.to_string())?;
+    let Some(stored_hash) = stored_hash.filter(|_| has_embedding) else {
+        return Ok(());
+    };
+    let changed = stored_hash != content_hash(&summary, &context, &memo);
+
+    let (next, from) = match status.as_str() {
+        "JARRED" | "RE_EXTRACT" if changed => ("RE_EMBED", Some(status.as_str())),
+        "DISTILLED" if changed => ("ON_STILL", None),
+        "RE_EMBED" if !changed => (requeued_from.as_deref().unwrap_or("JARRED"), None),
+        "ON_STILL" if !changed => ("DISTILLED", None),
+        _ => return Ok(()),
+    };
+    conn.execute(
+        "UPDATE mashes SET status = ?1, requeued_from = ?2, updated_at = ?3 WHERE id = ?4",
+        params![next, from, now_ms(), id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
 pub fn set_mash_status(conn: &Connection, id: &str, status: &str) -> Result<(), String> {
     let now = now_ms();
     conn.execute(
-        "UPDATE mashes SET status = ?1, updated_at = ?2 WHERE id = ?3",
+        "UPDATE mashes SET status = ?1, requeued_from = NULL, updated_at = ?2 WHERE id = ?3",
         params![status, now, id],
     )
     .map_err(|e| e.to_string())?;
@@ -175,7 +230,7 @@ pub fn reset_for_reembed(conn: &Connection) -> Result<u32, String> {
     let now = now_ms();
     let count = conn
         .execute(
-            "UPDATE mashes SET embedding = NULL, status = 'RE_EMBED', updated_at = ?1
+            "UPDATE mashes SET embedding = NULL, status = 'RE_EMBED', requeued_from = NULL, updated_at = ?1
              WHERE status IN ('DISTILLED', 'JARRED')",
             params![now],
         )
@@ -213,3 +268,263 @@ pub fn search_mashes(conn: &Connection, query: &str) -> Result<Vec<Mash>, String
         .map_err(|e| e.to_string())?;
     Ok(mashes)
 }
+
+pub fn get_mash(conn: &Connection, id: &str) -> Result<Mash, String> {
+    conn.query_row(
+        "SELECT id, type, status, summary, context, memo, created_at, updated_at
+         FROM mashes WHERE id = ?1",
+        params![id],
+        row_to_mash,
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => format!("Mash {} not found", id),
+        other => other.to_string(),
+    })
+}
+
+/// Join two text fields, skipping whichever side is empty or already contained
+/// in the other.
+fn combine_text(a: &str, b: &str) -> String {
+    let (a, b) = (a.trim(), b.trim());
+    if b.is_empty() || a.contains(b) {
+        a.to_string()
+    } else if a.is_empty() || b.contains(a) {
+        b.to_string()
+    } else {
+        format!("{}\n\n{}", a, b)
+    }
+}
+
+fn save_revision(conn: &Connection, mash_id: &str, mash: &Mash, reason: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO mash_revisions (mash_id, original_id, type, summary, context, memo,
+                                     reason, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            mash_id,
+            mash.id,
+            mash.mash_type,
+            mash.summary,
+            mash.context,
+            mash.memo,
+            reason,
+            now_ms()
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Move the per-mash bookkeeping of `from_id` to `to_id` before `from_id` is
+/// deleted, so dismissed, tried and resolved pairs are not proposed again. Rows
+/// `to_id` already has win; pairs that would join `to_id` to itself are dropped.
+fn repoint_mash_records(conn: &Connection, from_id: &str, to_id: &str) -> Result<(), String> {
+    let statements = [
+        // Stored as (smaller id, larger id)
+        "INSERT OR IGNORE INTO duplicate_dismissals (source_id, target_id, dismissed_at)
+         SELECT MIN(?2, other), MAX(?2, other), dismissed_at
+         FROM (
+             SELECT CASE WHEN source_id = ?1 THEN target_id ELSE source_id END AS other,
+                    dismissed_at
+             FROM duplicate_dismissals
+             WHERE source_id = ?1 OR target_id = ?1
+         )
+         WHERE other != ?2",
+        "INSERT OR IGNORE INTO link_prediction_attempts (source_id, target_id, score, attempted_at)
+         SELECT CASE WHEN source_id = ?1 THEN ?2 ELSE source_id END,
+                CASE WHEN target_id = ?1 THEN ?2 ELSE target_id END,
+                score, attempted_at
+         FROM link_prediction_attempts
+         WHERE (source_id = ?1 AND target_id != ?2) OR (target_id = ?1 AND source_id != ?2)",
+        "INSERT OR IGNORE INTO resolution_attempts (question_id, candidate_id, attempted_at)
+         SELECT CASE WHEN question_id = ?1 THEN ?2 ELSE question_id END,
+                CASE WHEN candidate_id = ?1 THEN ?2 ELSE candidate_id END,
+                attempted_at
+         FROM resolution_attempts
+         WHERE (question_id = ?1 AND candidate_id != ?2) OR (candidate_id = ?1 AND question_id != ?2)",
+        "INSERT OR IGNORE INTO review_states
+            (mash_id, ease, interval_days, repetitions, due_at, last_reviewed_at, last_grade)
+         SELECT ?2, ease, interval_days, repetitions, due_at, last_reviewed_at, last_grade
+         FROM review_states WHERE mash_id = ?1",
+        "INSERT OR IGNORE INTO question_resolutions (mash_id, resolved_by, resolved_at)
+         SELECT ?2, NULLIF(resolved_by, ?2), resolved_at
+         FROM question_resolutions WHERE mash_id = ?1",
+        "UPDATE question_resolutions SET resolved_by = ?2
+         WHERE resolved_by = ?1 AND mash_id != ?2",
+    ];
+    for sql in statements {
+        conn.execute(sql, params![from_id, to_id])
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Fold `merged_id` into `survivor_id`: both texts are snapshotted as revisions,
+/// the merged summary and memo land in the survivor's memo, contexts are joined,
+/// edges move to the survivor, and an already embedded survivor goes back to
+/// RE_EMBED so its vector reflects the combined text.
+pub fn merge_mashes(conn: &Connection, survivor_id: &str, merged_id: &str) -> Result<Mash, String> {
+    if survivor_id == merged_id {
+        return Err("A mash cannot be merged into itself".to_string());
+    }
+    let survivor = get_mash(conn, survivor_id)?;
+    let merged = get_mash(conn, merged_id)?;
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    save_revision(&tx, survivor_id, &survivor, "merge")?;
+    save_revision(&tx, survivor_id, &merged, "merge")?;
+    // Revisions the merged mash already carried would be lost with it.
+    tx.execute(
+        "UPDATE mash_revisions SET mash_id = ?1 WHERE mash_id = ?2",
+        params![survivor_id, merged_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let context = combine_text(&survivor.context, &merged.context);
+    let merged_note = if survivor.summary.trim() == merged.summary.trim() {
+        merged.memo.clone()
+    } else {
+        combine_text(&merged.summary, &merged.memo)
+    };
+    let memo = combine_text(&survivor.memo, &merged_note);
+
+    crate::db::edges::repoint_edges(&tx, merged_id, survivor_id)?;
+    repoint_mash_records(&tx, merged_id, survivor_id)?;
+    tx.execute("DELETE FROM mashes WHERE id = ?1", params![merged_id])
+        .map_err(|e| e.to_string())?;
+    tx.execute(
+        "UPDATE mashes SET context = ?1, memo = ?2, updated_at = ?3,
+                embedding = CASE WHEN status IN ('MASH_TUN', 'ON_STILL') THEN embedding ELSE NULL END,
+                status = CASE WHEN status IN ('MASH_TUN', 'ON_STILL') THEN status ELSE 'RE_EMBED' END
+         WHERE id = ?4",
+        params![context, memo, now_ms(), survivor_id],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    get_mash(conn, survivor_id)
+}
+
+/// Snapshots taken before destructive edits, newest first.
+pub fn get_mash_revisions(conn: &Connection, id: &str) -> Result<Vec<MashRevision>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, mash_id, original_id, type, summary, context, memo, reason, created_at
+             FROM mash_revisions WHERE mash_id = ?1
+             ORDER BY created_at DESC, id DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![id], |row| {
+            Ok(MashRevision {
+                id: row.get(0)?,
+                mash_id: row.get(1)?,
+                original_id: row.get(2)?,
+                mash_type: row.get(3)?,
+                summary: row.get(4)?,
+                context: row.get(5)?,
+                memo: row.get(6)?,
+                reason: row.get(7)?,
+                created_at: row.get(8)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Replace a mash with the given parts. Each part records the original as a
+/// revision and inherits the edges assigned to it; neighbors whose edge no part
+/// claimed go to RE_EXTRACT so their relations to the parts get rediscovered.
+/// Parts of an already processed mash are queued ON_STILL for embedding.
+pub fn split_mash(conn: &Connection, id: &str, parts: &[SplitPart]) -> Result<Vec<Mash>, String> {
+    if parts.len() < 2 {
+        return Err("A split needs at least two parts".to_string());
+    }
+    let original = get_mash(conn, id)?;
+    let edges = crate::db::edges::get_mash_edges(conn, id)?;
+    let status = if original.status == "MASH_TUN" {
+        "MASH_TUN"
+    } else {
+        "ON_STILL"
+    };
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let mut created = Vec::with_capacity(parts.len());
+    for part in parts {
+        let mash = add_mash(&tx, &part.mash_type, &part.summary, &part.context, &part.memo)?;
+        if status != "MASH_TUN" {
+            set_mash_status(&tx, &mash.id, status)?;
+        }
+        save_revision(&tx, &mash.id, &original, "split")?;
+        for (edge, _) in edges.iter().filter(|(e, _)| part.edge_ids.contains(&e.id)) {
+            let directed: bool = tx
+                .query_row(
+                    "SELECT COALESCE((SELECT directed FROM relation_types WHERE code = ?1), 0)",
+                    params![edge.relation_type],
+                    |row| row.get(0),
+                )
+                .map_err(|e| e.to_string())?;
+            let swap = |endpoint: &str| {
+                if endpoint == id {
+                    mash.id.clone()
+                } else {
+                    endpoint.to_string()
+                }
+            };
+            let (source_id, target_id) = (swap(&edge.source_id), swap(&edge.target_id));
+            let (source_id, target_id) =
+                crate::db::edges::normalize_endpoints(directed, &source_id, &target_id);
+            tx.execute(
+                "INSERT OR IGNORE INTO edges (source_id, target_id, relation_type, source, confidence,
+                                              rationale, model, extracted_at, review_state, evidence,
+                                              created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                params![
+                    source_id,
+                    target_id,
+                    edge.relation_type,
+                    edge.source,
+                    edge.confidence,
+                    edge.rationale,
+                    edge.model,
+                    edge.extracted_at,
+                    edge.review_state,
+                    edge.evidence,
+                    edge.created_at,
+                    now_ms()
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        created.push(get_mash(&tx, &mash.id)?);
+    }
+
+    let now = now_ms();
+    for (edge, _) in &edges {
+        if parts.iter().any(|p| p.edge_ids.contains(&edge.id)) {
+            continue;
+        }
+        let other = if edge.source_id == id {
+            &edge.target_id
+        } else {
+            &edge.source_id
+        };
+        tx.execute(
+            "UPDATE mashes SET status = 'RE_EXTRACT', updated_at = ?1
+             WHERE id = ?2 AND status = 'JARRED'",
+            params![now, other],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    // Older revisions of the original stay reachable from the first part.
+    tx.execute(
+        "UPDATE mash_revisions SET mash_id = ?1 WHERE mash_id = ?2",
+        params![created[0].id, id],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM mashes WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(created)
+}
@@ -1,7 +1,21 @@
+pub mod clusters;
+pub mod conflicts;
+pub mod conversations;
+pub mod digests;
+pub mod duplicates;
+pub mod edge_review;
 pub mod edges;
+pub mod embedding_cache;
+pub mod history;
+pub mod mash_types;
 pub mod mashes;
+pub mod questions;
+pub mod relation_types;
+pub mod reviews;
 pub mod search_cache;
 pub mod settings;
+pub mod timeline;
+pub mod usage;
 
 use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -22,6 +36,7 @@ pub fn init_db(app_data_dir: &Path) -> Connection {
     conn.pragma_update(None, "journal_mode", "WAL").ok();
     conn.pragma_update(None, "foreign_keys", "ON").ok();
     create_schema(&conn);
+    migrate(&conn);
     conn
 }
 
@@ -52,6 +67,10 @@ fn create_schema(conn: &Connection) {
             tokenize='trigram'
         );
 
+        CREATE VIRTUAL TABLE IF NOT EXISTS mashes_fts_vocab USING fts5vocab(
+            'mashes_fts', 'instance'
+        );
+
         CREATE TRIGGER IF NOT EXISTS mashes_ai AFTER INSERT ON mashes BEGIN
             INSERT INTO mashes_fts(rowid, summary, context, memo)
             VALUES (new.rowid, new.summary, new.context, new.memo);
@@ -93,7 +112,579 @@ fn create_schema(conn: &Connection) {
             created_at INTEGER NOT NULL
         );
         CREATE INDEX IF NOT EXISTS idx_search_cache_query ON search_cache(query, provider);
+
+        CREATE TABLE IF NOT EXISTS mash_types (
+            code TEXT PRIMARY KEY,
+            labels TEXT NOT NULL DEFAULT '{}',
+            description TEXT NOT NULL DEFAULT '',
+            position INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS relation_types (
+            code TEXT PRIMARY KEY,
+            description TEXT NOT NULL DEFAULT '',
+            directed INTEGER NOT NULL DEFAULT 0,
+            inverse_label TEXT UNIQUE,
+            position INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS edge_rejections (
+            source_id TEXT NOT NULL REFERENCES mashes(id) ON DELETE CASCADE,
+            target_id TEXT NOT NULL REFERENCES mashes(id) ON DELETE CASCADE,
+            relation_type TEXT NOT NULL,
+            rejected_at INTEGER NOT NULL,
+            PRIMARY KEY (source_id, target_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS clusters (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            size INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS mash_clusters (
+            mash_id TEXT PRIMARY KEY REFERENCES mashes(id) ON DELETE CASCADE,
+            cluster_id INTEGER NOT NULL REFERENCES clusters(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_mash_clusters_cluster ON mash_clusters(cluster_id);
+
+        CREATE TABLE IF NOT EXISTS link_prediction_attempts (
+            source_id TEXT NOT NULL REFERENCES mashes(id) ON DELETE CASCADE,
+            target_id TEXT NOT NULL REFERENCES mashes(id) ON DELETE CASCADE,
+            score REAL NOT NULL,
+            attempted_at INTEGER NOT NULL,
+            PRIMARY KEY (source_id, target_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS conflict_resolutions (
+            edge_id INTEGER PRIMARY KEY REFERENCES edges(id) ON DELETE CASCADE,
+            superseding_id TEXT REFERENCES mashes(id) ON DELETE SET NULL,
+            rationale TEXT NOT NULL DEFAULT '',
+            model TEXT NOT NULL,
+            proposed_at INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS mash_revisions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            mash_id TEXT NOT NULL REFERENCES mashes(id) ON DELETE CASCADE,
+            original_id TEXT NOT NULL,
+            type TEXT NOT NULL,
+            summary TEXT NOT NULL,
+            context TEXT NOT NULL DEFAULT '',
+            memo TEXT NOT NULL DEFAULT '',
+            reason TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_mash_revisions_mash ON mash_revisions(mash_id);
+
+        CREATE TABLE IF NOT EXISTS duplicate_dismissals (
+            source_id TEXT NOT NULL REFERENCES mashes(id) ON DELETE CASCADE,
+            target_id TEXT NOT NULL REFERENCES mashes(id) ON DELETE CASCADE,
+            dismissed_at INTEGER NOT NULL,
+            PRIMARY KEY (source_id, target_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS digests (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            period_start INTEGER NOT NULL,
+            period_end INTEGER NOT NULL,
+            markdown TEXT NOT NULL,
+            model TEXT,
+            created_at INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS conversations (
+            id TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS conversation_messages (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+            role TEXT NOT NULL,
+            content TEXT NOT NULL,
+            cited_ids TEXT NOT NULL DEFAULT '[]',
+            model TEXT,
+            created_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_conversation_messages_conversation
+            ON conversation_messages(conversation_id, id);
+
+        CREATE TABLE IF NOT EXISTS question_resolutions (
+            mash_id TEXT PRIMARY KEY REFERENCES mashes(id) ON DELETE CASCADE,
+            resolved_by TEXT REFERENCES mashes(id) ON DELETE SET NULL,
+            resolved_at INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS resolution_attempts (
+            question_id TEXT NOT NULL REFERENCES mashes(id) ON DELETE CASCADE,
+            candidate_id TEXT NOT NULL REFERENCES mashes(id) ON DELETE CASCADE,
+            attempted_at INTEGER NOT NULL,
+            PRIMARY KEY (question_id, candidate_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS review_states (
+            mash_id TEXT PRIMARY KEY REFERENCES mashes(id) ON DELETE CASCADE,
+            ease REAL NOT NULL,
+            interval_days REAL NOT NULL,
+            repetitions INTEGER NOT NULL,
+            due_at INTEGER NOT NULL,
+            last_reviewed_at INTEGER,
+            last_grade INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_review_states_due ON review_states(due_at);
+
+        CREATE TABLE IF NOT EXISTS embedding_cache (
+            provider TEXT NOT NULL,
+            model TEXT NOT NULL,
+            dimensions INTEGER NOT NULL,
+            text_hash TEXT NOT NULL,
+            embedding BLOB NOT NULL,
+            created_at INTEGER NOT NULL,
+            last_used_at INTEGER NOT NULL,
+            PRIMARY KEY (provider, model, dimensions, text_hash)
+        );
+        CREATE INDEX IF NOT EXISTS idx_embedding_cache_used ON embedding_cache(last_used_at);
+
+        CREATE TABLE IF NOT EXISTS api_usage (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            called_at INTEGER NOT NULL,
+            provider TEXT NOT NULL,
+            model TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            phase TEXT NOT NULL,
+            run_id TEXT,
+            prompt_tokens INTEGER NOT NULL,
+            completion_tokens INTEGER NOT NULL,
+            estimated INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE INDEX IF NOT EXISTS idx_api_usage_called ON api_usage(called_at);
         ",
     )
     .expect("failed to create schema");
 }
+
+/// Versioned data migrations, tracked with `PRAGMA user_version`.
+/// Each step runs once, in order, inside its own transaction.
+fn migrate(conn: &Connection) {
+    let version: i64 = conn
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .expect("failed to read schema version");
+
+    let steps: &[fn(&Connection) -> rusqlite::Result<()>] = &[
+        migrate_v1_mash_type_codes,
+        migrate_v2_relation_types,
+        migrate_v3_edge_provenance,
+        migrate_v4_typed_edge_keys,
+        migrate_v5_edge_review_state,
+        migrate_v6_cluster_summaries,
+        migrate_v7_edge_evidence,
+        migrate_v8_history,
+        migrate_v9_resolved_by,
+        migrate_v10_content_hash,
+        migrate_v11_history_rounding,
+        migrate_v12_requeued_from,
+        migrate_v13_mash_type_roles,
+    ];
+
+    for (i, step) in steps.iter().enumerate().skip(version as usize) {
+        let target = (i + 1) as i64;
+        conn.execute_batch("BEGIN").expect("failed to begin migration");
+        match step(conn) {
+            Ok(()) => {
+                conn.pragma_update(None, "user_version", target)
+                    .expect("failed to update schema version");
+                conn.execute_batch("COMMIT").expect("failed to commit migration");
+                log::info!("Database migrated to version {}", target);
+            }
+            Err(e) => {
+                let _ = conn.execute_batch("ROLLBACK");
+                panic!("migration to version {} failed: {}", target, e);
+            }
+        }
+    }
+}
+
+/// v1: seed the built-in mash types and move existing mashes from the
+/// hard-coded Korean names to language-neutral codes.
+fn migrate_v1_mash_type_codes(conn: &Connection) -> rusqlite::Result<()> {
+    let now = now_ms();
+    for (position, (code, legacy, en, description)) in mash_types::BUILTIN_TYPES.iter().enumerate() {
+        let labels = serde_json::json!({ "en": en, "ko": legacy }).to_string();
+        conn.execute(
+            "INSERT OR IGNORE INTO mash_types (code, labels, description, position, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![code, labels, description, position as i64, now, now],
+        )?;
+        conn.execute(
+            "UPDATE mashes SET type = ?1 WHERE type = ?2",
+            rusqlite::params![code, legacy],
+        )?;
+    }
+    Ok(())
+}
+
+/// v2: seed the built-in relation vocabulary.
+fn migrate_v2_relation_types(conn: &Connection) -> rusqlite::Result<()> {
+    let now = now_ms();
+    for (position, (code, description, directed, inverse)) in
+        relation_types::BUILTIN_RELATIONS.iter().enumerate()
+    {
+        conn.execute(
+            "INSERT OR IGNORE INTO relation_types
+                (code, description, directed, inverse_label, position, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![code, description, directed, inverse, position as i64, now, now],
+        )?;
+    }
+    Ok(())
+}
+
+/// v3: record why the AI proposed an edge, which chat model did it and when.
+fn migrate_v3_edge_provenance(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE edges ADD COLUMN rationale TEXT;
+         ALTER TABLE edges ADD COLUMN model TEXT;
+         ALTER TABLE edges ADD COLUMN extracted_at INTEGER;",
+    )
+}
+
+/// v4: key edges by (source, target, relation_type) so several typed relations can
+/// coexist on one pair. Symmetric relations are folded onto one canonical direction;
+/// where that produces duplicates, manual edges win over AI ones, then the newest.
+fn migrate_v4_typed_edge_keys(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE edges_v4 (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            source_id TEXT NOT NULL REFERENCES mashes(id) ON DELETE CASCADE,
+            target_id TEXT NOT NULL REFERENCES mashes(id) ON DELETE CASCADE,
+            relation_type TEXT NOT NULL,
+            source TEXT NOT NULL DEFAULT 'ai',
+            confidence REAL NOT NULL DEFAULT 0.0,
+            rationale TEXT,
+            model TEXT,
+            extracted_at INTEGER,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            UNIQUE(source_id, target_id, relation_type)
+        );
+
+        INSERT OR IGNORE INTO edges_v4
+            (id, source_id, target_id, relation_type, source, confidence,
+             rationale, model, extracted_at, created_at, updated_at)
+        SELECT e.id,
+               CASE WHEN COALESCE(rt.directed, 0) = 0 AND e.source_id > e.target_id
+                    THEN e.target_id ELSE e.source_id END,
+               CASE WHEN COALESCE(rt.directed, 0) = 0 AND e.source_id > e.target_id
+                    THEN e.source_id ELSE e.target_id END,
+               e.relation_type, e.source, e.confidence,
+               e.rationale, e.model, e.extracted_at, e.created_at, e.updated_at
+        FROM edges e
+        LEFT JOIN relation_types rt ON rt.code = e.relation_type
+        WHERE e.source_id != e.target_id
+        ORDER BY (e.source = 'ai'), e.updated_at DESC;
+
+        DROP TABLE edges;
+        ALTER TABLE edges_v4 RENAME TO edges;
+        CREATE INDEX IF NOT EXISTS idx_edges_source ON edges(source_id);
+        CREATE INDEX IF NOT EXISTS idx_edges_target ON edges(target_id);",
+    )
+}
+
+/// v5: AI edges go through review. Existing AI edges start out pending;
+/// manual edges are accepted by definition.
+fn migrate_v5_edge_review_state(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE edges ADD COLUMN review_state TEXT NOT NULL DEFAULT 'accepted';
+         UPDATE edges SET review_state = 'pending' WHERE source = 'ai';",
+    )
+}
+
+/// v6: cache an AI title and summary per cluster, with the membership it was written for.
+fn migrate_v6_cluster_summaries(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE clusters ADD COLUMN title TEXT;
+         ALTER TABLE clusters ADD COLUMN summary TEXT;
+         ALTER TABLE clusters ADD COLUMN summary_members TEXT;
+         ALTER TABLE clusters ADD COLUMN summary_model TEXT;
+         ALTER TABLE clusters ADD COLUMN summarized_at INTEGER;",
+    )
+}
+
+/// v7: keep the structural evidence (e.g. shared neighbors) behind edges that
+/// link prediction proposed.
+fn migrate_v7_edge_evidence(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch("ALTER TABLE edges ADD COLUMN evidence TEXT;")
+}
+
+/// v8: keep every version of mashes and edges with the interval it was valid
+/// for, so the graph can be rebuilt as of any past moment. Rows that already
+/// exist are seeded as valid since their creation.
+fn migrate_v8_history(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS mash_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            mash_id TEXT NOT NULL,
+            type TEXT NOT NULL,
+            status TEXT NOT NULL,
+            summary TEXT NOT NULL,
+            context TEXT NOT NULL,
+            memo TEXT NOT NULL,
+            valid_from INTEGER NOT NULL,
+            valid_to INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_mash_history_mash ON mash_history(mash_id, valid_to);
+        CREATE INDEX IF NOT EXISTS idx_mash_history_time ON mash_history(valid_from, valid_to);
+
+        CREATE TABLE IF NOT EXISTS edge_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            edge_id INTEGER NOT NULL,
+            source_id TEXT NOT NULL,
+            target_id TEXT NOT NULL,
+            relation_type TEXT NOT NULL,
+            source TEXT NOT NULL,
+            confidence REAL NOT NULL,
+            review_state TEXT NOT NULL,
+            valid_from INTEGER NOT NULL,
+            valid_to INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_edge_history_edge ON edge_history(edge_id, valid_to);
+        CREATE INDEX IF NOT EXISTS idx_edge_history_time ON edge_history(valid_from, valid_to);
+
+        INSERT INTO mash_history (mash_id, type, status, summary, context, memo, valid_from)
+        SELECT id, type, status, summary, context, memo, created_at FROM mashes;
+        INSERT INTO edge_history (edge_id, source_id, target_id, relation_type, source,
+                                  confidence, review_state, valid_from)
+        SELECT id, source_id, target_id, relation_type, source, confidence, review_state, created_at
+        FROM edges;
+
+        CREATE TRIGGER IF NOT EXISTS mashes_history_ai AFTER INSERT ON mashes BEGIN
+            INSERT INTO mash_history (mash_id, type, status, summary, context, memo, valid_from)
+            VALUES (new.id, new.type, new.status, new.summary, new.context, new.memo,
+                    CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS mashes_history_au AFTER UPDATE ON mashes
+        WHEN old.type IS NOT new.type OR old.status IS NOT new.status
+          OR old.summary IS NOT new.summary OR old.context IS NOT new.context
+          OR old.memo IS NOT new.memo
+        BEGIN
+            UPDATE mash_history
+            SET valid_to = CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)
+            WHERE mash_id = old.id AND valid_to IS NULL;
+            INSERT INTO mash_history (mash_id, type, status, summary, context, memo, valid_from)
+            VALUES (new.id, new.type, new.status, new.summary, new.context, new.memo,
+                    CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS mashes_history_ad AFTER DELETE ON mashes BEGIN
+            UPDATE mash_history
+            SET valid_to = CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)
+            WHERE mash_id = old.id AND valid_to IS NULL;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS edges_history_ai AFTER INSERT ON edges BEGIN
+            INSERT INTO edge_history (edge_id, source_id, target_id, relation_type, source,
+                                      confidence, review_state, valid_from)
+            VALUES (new.id, new.source_id, new.target_id, new.relation_type, new.source,
+                    new.confidence, new.review_state,
+                    CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS edges_history_au AFTER UPDATE ON edges
+        WHEN old.source_id IS NOT new.source_id OR old.target_id IS NOT new.target_id
+          OR old.relation_type IS NOT new.relation_type OR old.source IS NOT new.source
+          OR old.confidence IS NOT new.confidence OR old.review_state IS NOT new.review_state
+        BEGIN
+            UPDATE edge_history
+            SET valid_to = CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)
+            WHERE edge_id = old.id AND valid_to IS NULL;
+            INSERT INTO edge_history (edge_id, source_id, target_id, relation_type, source,
+                                      confidence, review_state, valid_from)
+            VALUES (new.id, new.source_id, new.target_id, new.relation_type, new.source,
+                    new.confidence, new.review_state,
+                    CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS edges_history_ad AFTER DELETE ON edges BEGIN
+            UPDATE edge_history
+            SET valid_to = CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)
+            WHERE edge_id = old.id AND valid_to IS NULL;
+        END;",
+    )
+}
+
+/// v9: add RESOLVED_BY to existing registries, and close a question or problem
+/// as soon as a RESOLVED_BY edge from it is accepted.
+fn migrate_v9_resolved_by(conn: &Connection) -> rusqlite::Result<()> {
+    let now = now_ms();
+    if let Some((code, description, directed, inverse)) = relation_types::BUILTIN_RELATIONS
+        .iter()
+        .find(|(code, ..)| *code == "RESOLVED_BY")
+    {
+        conn.execute(
+            "INSERT OR IGNORE INTO relation_types
+                (code, description, directed, inverse_label, position, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4,
+                     (SELECT COALESCE(MAX(position), -1) + 1 FROM relation_types), ?5, ?5)",
+            rusqlite::params![code, description, directed, inverse, now],
+        )?;
+    }
+    conn.execute_batch(
+        "CREATE TRIGGER IF NOT EXISTS edges_resolve_ai AFTER INSERT ON edges
+        WHEN new.relation_type = 'RESOLVED_BY' AND new.review_state = 'accepted'
+        BEGIN
+            INSERT OR IGNORE INTO question_resolutions (mash_id, resolved_by, resolved_at)
+            SELECT id, new.target_id, new.updated_at FROM mashes
+            WHERE id = new.source_id AND type IN ('question', 'problem');
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS edges_resolve_au AFTER UPDATE ON edges
+        WHEN new.relation_type = 'RESOLVED_BY' AND new.review_state = 'accepted'
+          AND (old.relation_type IS NOT new.relation_type OR old.review_state IS NOT new.review_state)
+        BEGIN
+            INSERT OR IGNORE INTO question_resolutions (mash_id, resolved_by, resolved_at)
+            SELECT id, new.target_id, new.updated_at FROM mashes
+            WHERE id = new.source_id AND type IN ('question', 'problem');
+        END;",
+    )
+}
+
+/// v10: remember which text each embedding was computed from, so edits can be
+/// told apart from no-op saves. Existing embeddings are assumed current.
+fn migrate_v10_content_hash(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch("ALTER TABLE mashes ADD COLUMN content_hash TEXT;")?;
+    let rows: Vec<(String, String, String, String)> = {
+        let mut stmt = conn.prepare(
+            "SELECT id, summary, context, memo FROM mashes WHERE embedding IS NOT NULL",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()?
+    };
+    for (id, summary, context, memo) in rows {
+        conn.execute(
+            "UPDATE mashes SET content_hash = ?1 WHERE id = ?2",
+            rusqlite::params![crate::similarity::content_hash(&summary, &context, &memo), id],
+        )?;
+    }
+    Ok(())
+}
+
+/// History triggers as v8 created them, but with timestamps rounded to whole
+/// milliseconds so they match `now_ms()` written by the app.
+const HISTORY_TRIGGERS: &str = "
+    CREATE TRIGGER IF NOT EXISTS mashes_history_ai AFTER INSERT ON mashes BEGIN
+        INSERT INTO mash_history (mash_id, type, status, summary, context, memo, valid_from)
+        VALUES (new.id, new.type, new.status, new.summary, new.context, new.memo,
+                CAST(ROUND((julianday('now') - 2440587.5) * 86400000) AS INTEGER));
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS mashes_history_au AFTER UPDATE ON mashes
+    WHEN old.type IS NOT new.type OR old.status IS NOT new.status
+      OR old.summary IS NOT new.summary OR old.context IS NOT new.context
+      OR old.memo IS NOT new.memo
+    BEGIN
+        UPDATE mash_history
+        SET valid_to = CAST(ROUND((julianday('now') - 2440587.5) * 86400000) AS INTEGER)
+        WHERE mash_id = old.id AND valid_to IS NULL;
+        INSERT INTO mash_history (mash_id, type, status, summary, context, memo, valid_from)
+        VALUES (new.id, new.type, new.status, new.summary, new.context, new.memo,
+                CAST(ROUND((julianday('now') - 2440587.5) * 86400000) AS INTEGER));
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS mashes_history_ad AFTER DELETE ON mashes BEGIN
+        UPDATE mash_history
+        SET valid_to = CAST(ROUND((julianday('now') - 2440587.5) * 86400000) AS INTEGER)
+        WHERE mash_id = old.id AND valid_to IS NULL;
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS edges_history_ai AFTER INSERT ON edges BEGIN
+        INSERT INTO edge_history (edge_id, source_id, target_id, relation_type, source,
+                                  confidence, review_state, valid_from)
+        VALUES (new.id, new.source_id, new.target_id, new.relation_type, new.source,
+                new.confidence, new.review_state,
+                CAST(ROUND((julianday('now') - 2440587.5) * 86400000) AS INTEGER));
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS edges_history_au AFTER UPDATE ON edges
+    WHEN old.source_id IS NOT new.source_id OR old.target_id IS NOT new.target_id
+      OR old.relation_type IS NOT new.relation_type OR old.source IS NOT new.source
+      OR old.confidence IS NOT new.confidence OR old.review_state IS NOT new.review_state
+    BEGIN
+        UPDATE edge_history
+        SET valid_to = CAST(ROUND((julianday('now') - 2440587.5) * 86400000) AS INTEGER)
+        WHERE edge_id = old.id AND valid_to IS NULL;
+        INSERT INTO edge_history (edge_id, source_id, target_id, relation_type, source,
+                                  confidence, review_state, valid_from)
+        VALUES (new.id, new.source_id, new.target_id, new.relation_type, new.source,
+                new.confidence, new.review_state,
+                CAST(ROUND((julianday('now') - 2440587.5) * 86400000) AS INTEGER));
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS edges_history_ad AFTER DELETE ON edges BEGIN
+        UPDATE edge_history
+        SET valid_to = CAST(ROUND((julianday('now') - 2440587.5) * 86400000) AS INTEGER)
+        WHERE edge_id = old.id AND valid_to IS NULL;
+    END;";
+
+/// v11: v8's history triggers truncate timestamps; replace them with rounding ones.
+fn migrate_v11_history_rounding(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "DROP TRIGGER IF EXISTS mashes_history_ai;
+        DROP TRIGGER IF EXISTS mashes_history_au;
+        DROP TRIGGER IF EXISTS mashes_history_ad;
+        DROP TRIGGER IF EXISTS edges_history_ai;
+        DROP TRIGGER IF EXISTS edges_history_au;
+        DROP TRIGGER IF EXISTS edges_history_ad;",
+    )?;
+    conn.execute_batch(HISTORY_TRIGGERS)
+}
+
+/// v12: remember the status an edit moved a mash away from, so restoring the
+/// text returns it there (a pending re-extraction included).
+fn migrate_v12_requeued_from(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch("ALTER TABLE mashes ADD COLUMN requeued_from TEXT;")
+}
+
+/// v13: give mash types a role, so open-question tracking follows the
+/// configured taxonomy instead of fixed codes, and make the resolution
+/// triggers read it.
+fn migrate_v13_mash_type_roles(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch("ALTER TABLE mash_types ADD COLUMN role TEXT;")?;
+    for (code, role) in mash_types::BUILTIN_ROLES {
+        conn.execute(
+            "UPDATE mash_types SET role = ?2 WHERE code = ?1",
+            rusqlite::params![code, role],
+        )?;
+    }
+    conn.execute_batch(
+        "DROP TRIGGER IF EXISTS edges_resolve_ai;
+        DROP TRIGGER IF EXISTS edges_resolve_au;
+
+        CREATE TRIGGER edges_resolve_ai AFTER INSERT ON edges
+        WHEN new.relation_type = 'RESOLVED_BY' AND new.review_state = 'accepted'
+        BEGIN
+            INSERT OR IGNORE INTO question_resolutions (mash_id, resolved_by, resolved_at)
+            SELECT m.id, new.target_id, new.updated_at
+            FROM mashes m JOIN mash_types t ON t.code = m.type
+            WHERE m.id = new.source_id AND t.role = 'open';
+        END;
+
+        CREATE TRIGGER edges_resolve_au AFTER UPDATE ON edges
+        WHEN new.relation_type = 'RESOLVED_BY' AND new.review_state = 'accepted'
+          AND (old.relation_type IS NOT new.relation_type OR old.review_state IS NOT new.review_state)
+        BEGIN
+            INSERT OR IGNORE INTO question_resolutions (mash_id, resolved_by, resolved_at)
+            SELECT m.id, new.target_id, new.updated_at
+            FROM mashes m JOIN mash_types t ON t.code = m.type
+            WHERE m.id = new.source_id AND t.role = 'open';
+        END;",
+    )
+}
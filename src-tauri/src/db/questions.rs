@@ -0,0 +1,209 @@
+use std::collections::{HashMap, HashSet};
+
+use rusqlite::{params, Connection};
+
+use crate::ai::relationship::RelationCandidate;
+use crate::db::edge_review::{get_rejected_pairs, is_pair_rejected};
+use crate::db::now_ms;
+use crate::models::{OpenQuestion, ResolutionCandidate};
+use crate::similarity::{cosine_similarity, deserialize_embedding};
+
+const DAY_MS: f64 = 24.0 * 60.0 * 60.0 * 1000.0;
+
+struct EmbeddedMash {
+    id: String,
+    summary: String,
+    embedding: Vec<f32>,
+}
+
+/// Record `id` as resolved, optionally by the mash that answers it.
+pub fn resolve_question(conn: &Connection, id: &str, resolved_by: Option<&str>) -> Result<(), String> {
+    let mash = crate::db::mashes::get_mash(conn, id)?;
+    if crate::db::mash_types::get_role(conn, &mash.mash_type)?.as_deref() != Some("open") {
+        return Err(format!("Mash {} is a {}, not a question or problem", id, mash.mash_type));
+    }
+    if let Some(by) = resolved_by {
+        if by == id {
+            return Err("A mash cannot resolve itself".to_string());
+        }
+        crate::db::mashes::get_mash(conn, by)?;
+    }
+    conn.execute(
+        "INSERT OR REPLACE INTO question_resolutions (mash_id, resolved_by, resolved_at)
+         VALUES (?1, ?2, ?3)",
+        params![id, resolved_by, now_ms()],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn reopen_question(conn: &Connection, id: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM question_resolutions WHERE mash_id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Unresolved JARRED mashes of an `open` type (questions and problems by
+/// default) with their pending RESOLVED_BY proposals, oldest first. Centrality and score are left at zero.
+pub fn get_open_questions(conn: &Connection) -> Result<Vec<OpenQuestion>, String> {
+    let now = now_ms();
+    let mut stmt = conn
+        .prepare(
+            "SELECT m.id, m.type, m.summary, m.created_at
+             FROM mashes m
+             WHERE m.status = 'JARRED'
+               AND m.type IN (SELECT code FROM mash_types WHERE role = 'open')
+               AND NOT EXISTS (SELECT 1 FROM question_resolutions r WHERE r.mash_id = m.id)
+             ORDER BY m.created_at",
+        )
+        .map_err(|e| e.to_string())?;
+    let mut questions = stmt
+        .query_map([], |row| {
+            let created_at: i64 = row.get(3)?;
+            Ok(OpenQuestion {
+                id: row.get(0)?,
+                mash_type: row.get(1)?,
+                summary: row.get(2)?,
+                created_at,
+                age_days: (now - created_at).max(0) as f64 / DAY_MS,
+                pagerank: 0.0,
+                score: 0.0,
+                candidates: vec![],
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT e.id, e.target_id, t.summary, e.confidence, e.rationale
+             FROM edges e JOIN mashes t ON t.id = e.target_id
+             WHERE e.source_id = ?1 AND e.relation_type = 'RESOLVED_BY'
+               AND e.review_state = 'pending'
+             ORDER BY e.confidence DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    for question in &mut questions {
+        question.candidates = stmt
+            .query_map(params![question.id], |row| {
+                Ok(ResolutionCandidate {
+                    edge_id: row.get(0)?,
+                    mash_id: row.get(1)?,
+                    summary: row.get(2)?,
+                    confidence: row.get(3)?,
+                    rationale: row.get(4)?,
+                })
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(questions)
+}
+
+/// Score each question by age weighted by PageRank relative to the average
+/// node, so an old question in the middle of the graph comes first.
+pub fn rank_open_questions(questions: &mut [OpenQuestion], pagerank: &HashMap<String, f64>) {
+    let n = pagerank.len().max(1) as f64;
+    for q in questions.iter_mut() {
+        q.pagerank = pagerank.get(&q.id).copied().unwrap_or(0.0);
+        q.score = q.age_days.max(1.0) * (1.0 + q.pagerank * n);
+    }
+    questions.sort_by(|a, b| b.score.total_cmp(&a.score));
+}
+
+/// Pair open questions with the most similar JARRED mashes of a `decision` or
+/// `answer` type (decisions and insights by default).
+/// Pairs already tried, rejected, or already linked by RESOLVED_BY are skipped.
+pub fn resolution_candidates(
+    conn: &Connection,
+    top_k: usize,
+    threshold: f32,
+    limit: usize,
+) -> Result<Vec<RelationCandidate>, String> {
+    let load = |roles: &str| -> Result<Vec<EmbeddedMash>, String> {
+        let sql = format!(
+            "SELECT m.id, m.summary, m.embedding FROM mashes m
+             WHERE m.status = 'JARRED' AND m.embedding IS NOT NULL
+               AND m.type IN (SELECT code FROM mash_types WHERE role IN ({}))
+               AND NOT EXISTS (SELECT 1 FROM question_resolutions r WHERE r.mash_id = m.id)
+             ORDER BY m.created_at",
+            roles
+        );
+        let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                let blob: Vec<u8> = row.get(2)?;
+                Ok(EmbeddedMash {
+                    id: row.get(0)?,
+                    summary: row.get(1)?,
+                    embedding: deserialize_embedding(&blob),
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())
+    };
+    let questions = load("'open'")?;
+    let answers = load("'decision', 'answer'")?;
+    if questions.is_empty() || answers.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut skip: HashSet<(String, String)> = HashSet::new();
+    for sql in [
+        "SELECT question_id, candidate_id FROM resolution_attempts",
+        "SELECT source_id, target_id FROM edges WHERE relation_type = 'RESOLVED_BY'",
+    ] {
+        let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?;
+        for row in rows {
+            skip.insert(row.map_err(|e| e.to_string())?);
+        }
+    }
+    let rejected = get_rejected_pairs(conn)?;
+
+    let mut candidates = Vec::new();
+    for q in &questions {
+        let mut scored: Vec<(f32, &EmbeddedMash)> = answers
+            .iter()
+            .filter(|a| {
+                !skip.contains(&(q.id.clone(), a.id.clone())) && !is_pair_rejected(&rejected, &q.id, &a.id)
+            })
+            .map(|a| (cosine_similarity(&q.embedding, &a.embedding), a))
+            .filter(|(sim, _)| *sim >= threshold)
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        for (_, a) in scored.into_iter().take(top_k) {
+            candidates.push(RelationCandidate {
+                source_id: q.id.clone(),
+                source_summary: q.summary.clone(),
+                target_id: a.id.clone(),
+                target_summary: a.summary.clone(),
+                evidence: None,
+            });
+        }
+        if candidates.len() >= limit {
+            break;
+        }
+    }
+    candidates.truncate(limit);
+    Ok(candidates)
+}
+
+/// Remember proposed pairs so they are not sent to the model again.
+pub fn record_resolution_attempts(conn: &Connection, candidates: &[RelationCandidate]) -> Result<(), String> {
+    let now = now_ms();
+    for c in candidates {
+        conn.execute(
+            "INSERT OR REPLACE INTO resolution_attempts (question_id, candidate_id, attempted_at)
+             VALUES (?1, ?2, ?3)",
+            params![c.source_id, c.target_id, now],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
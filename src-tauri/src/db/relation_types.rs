@@ -0,0 +1,185 @@
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::db::now_ms;
+use crate::models::RelationTypeDef;
+
+/// Built-in vocabulary seeded on first run: (code, description, directed, inverse label).
+pub const BUILTIN_RELATIONS: &[(&str, &str, bool, Option<&str>)] = &[
+    (
+        "RELATED_TO",
+        "items share a common topic, context, or are part of the same domain/project",
+        false,
+        None,
+    ),
+    (
+        "SUPPORTS",
+        "source reinforces, extends, or provides evidence for target",
+        true,
+        Some("SUPPORTED_BY"),
+    ),
+    (
+        "CONFLICTS_WITH",
+        "source contradicts or creates tension with target",
+        false,
+        None,
+    ),
+    (
+        "RESOLVED_BY",
+        "source (a question or problem) is answered or solved by target (a decision or insight)",
+        true,
+        Some("RESOLVES"),
+    ),
+];
+
+fn row_to_relation_type(row: &rusqlite::Row) -> rusqlite::Result<RelationTypeDef> {
+    Ok(RelationTypeDef {
+        code: row.get(0)?,
+        description: row.get(1)?,
+        directed: row.get(2)?,
+        inverse_label: row.get(3)?,
+        position: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+    })
+}
+
+/// Labels are shown on the graph and matched against AI output, so keep them SCREAMING_SNAKE_CASE.
+fn validate_label(label: &str) -> Result<(), String> {
+    // Edge ids on the frontend are `source__target__relation`, split on "__"
+    let valid = !label.is_empty()
+        && label.len() <= 32
+        && !label.contains("__")
+        && label
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid relation label '{}': use 1-32 uppercase letters, digits or single '_'",
+            label
+        ))
+    }
+}
+
+/// Map a label onto a registered relation. Returns the canonical code and whether
+/// the label was the inverse name, in which case source and target must be swapped.
+pub fn resolve_relation_label<'a>(
+    types: &'a [RelationTypeDef],
+    label: &str,
+) -> Option<(&'a str, bool)> {
+    types.iter().find_map(|t| {
+        if t.code == label {
+            Some((t.code.as_str(), false))
+        } else if t.directed && t.inverse_label.as_deref() == Some(label) {
+            Some((t.code.as_str(), true))
+        } else {
+            None
+        }
+    })
+}
+
+pub fn get_relation_types(conn: &Connection) -> Result<Vec<RelationTypeDef>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT code, description, directed, inverse_label, position, created_at, updated_at
+             FROM relation_types ORDER BY position, code",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], row_to_relation_type)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+pub fn get_relation_type(conn: &Connection, code: &str) -> Result<RelationTypeDef, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT code, description, directed, inverse_label, position, created_at, updated_at
+             FROM relation_types WHERE code = ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    stmt.query_row(params![code], row_to_relation_type)
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Unknown relation type: '{}'", code))
+}
+
+/// Insert a new relation type or update an existing one.
+/// Symmetric relations have no inverse label; directed ones require it.
+pub fn save_relation_type(
+    conn: &Connection,
+    code: &str,
+    description: &str,
+    directed: bool,
+    inverse_label: Option<&str>,
+) -> Result<RelationTypeDef, String> {
+    validate_label(code)?;
+    if description.trim().is_empty() {
+        return Err("Relation type description is required".to_string());
+    }
+    let inverse_label = if directed {
+        let inverse = inverse_label
+            .filter(|l| !l.is_empty())
+            .ok_or_else(|| "Directed relation types need an inverse label".to_string())?;
+        validate_label(inverse)?;
+        if inverse == code {
+            return Err("Inverse label must differ from the relation code".to_string());
+        }
+        Some(inverse)
+    } else {
+        None
+    };
+
+    // Codes and inverse labels share one namespace so AI output resolves unambiguously.
+    let mut clash_stmt = conn
+        .prepare(
+            "SELECT code FROM relation_types
+             WHERE code != ?1 AND (code = ?2 OR inverse_label = ?1 OR inverse_label = ?2)",
+        )
+        .map_err(|e| e.to_string())?;
+    let clash: Option<String> = clash_stmt
+        .query_row(params![code, inverse_label.unwrap_or(code)], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if let Some(other) = clash {
+        return Err(format!("Label conflicts with relation type '{}'", other));
+    }
+
+    let now = now_ms();
+    conn.execute(
+        "INSERT INTO relation_types (code, description, directed, inverse_label, position, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, (SELECT COALESCE(MAX(position), -1) + 1 FROM relation_types), ?5, ?6)
+         ON CONFLICT(code) DO UPDATE SET
+            description = excluded.description,
+            directed = excluded.directed,
+            inverse_label = excluded.inverse_label,
+            updated_at = excluded.updated_at",
+        params![code, description.trim(), directed, inverse_label, now, now],
+    )
+    .map_err(|e| e.to_string())?;
+
+    get_relation_type(conn, code)
+}
+
+/// Delete a relation type. Refuses while any edge still uses it.
+pub fn delete_relation_type(conn: &Connection, code: &str) -> Result<(), String> {
+    let in_use: u32 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM edges WHERE relation_type = ?1",
+            params![code],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if in_use > 0 {
+        return Err(format!(
+            "Relation type '{}' is used by {} edges; retype them first",
+            code, in_use
+        ));
+    }
+
+    conn.execute("DELETE FROM relation_types WHERE code = ?1", params![code])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
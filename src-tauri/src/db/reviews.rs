@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::db::now_ms;
+use crate::models::{DueReview, ReviewState};
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+const INITIAL_EASE: f64 = 2.5;
+const MIN_EASE: f64 = 1.3;
+
+/// Mashes in a CONFLICTS_WITH edge count this much more when prioritizing.
+const CONFLICT_BOOST: f64 = 2.0;
+
+fn row_to_review_state(row: &rusqlite::Row) -> rusqlite::Result<ReviewState> {
+    Ok(ReviewState {
+        mash_id: row.get(0)?,
+        ease: row.get(1)?,
+        interval_days: row.get(2)?,
+        repetitions: row.get(3)?,
+        due_at: row.get(4)?,
+        last_reviewed_at: row.get(5)?,
+        last_grade: row.get(6)?,
+    })
+}
+
+pub fn get_review_state(conn: &Connection, mash_id: &str) -> Result<Option<ReviewState>, String> {
+    conn.query_row(
+        "SELECT mash_id, ease, interval_days, repetitions, due_at, last_reviewed_at, last_grade
+         FROM review_states WHERE mash_id = ?1",
+        params![mash_id],
+        row_to_review_state,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// SM-2: a grade of 3 or more (0–5) grows the interval, anything lower starts
+/// the mash over at one day. Ease moves with every grade but never below 1.3.
+fn schedule(prev: Option<&ReviewState>, grade: u8) -> (f64, f64, u32) {
+    let (ease, interval, reps) = prev
+        .map(|s| (s.ease, s.interval_days, s.repetitions))
+        .unwrap_or((INITIAL_EASE, 0.0, 0));
+    let q = grade as f64;
+    let ease = (ease + 0.1 - (5.0 - q) * (0.08 + (5.0 - q) * 0.02)).max(MIN_EASE);
+    if grade < 3 {
+        return (ease, 1.0, 0);
+    }
+    let interval = match reps {
+        0 => 1.0,
+        1 => 6.0,
+        _ => (interval * ease).round(),
+    };
+    (ease, interval, reps + 1)
+}
+
+/// Grade a review of a JARRED mash and schedule the next one.
+pub fn record_review(conn: &Connection, mash_id: &str, grade: u8) -> Result<ReviewState, String> {
+    if grade > 5 {
+        return Err(format!("Grade must be between 0 and 5, got {}", grade));
+    }
+    let mash = crate::db::mashes::get_mash(conn, mash_id)?;
+    if mash.status != "JARRED" {
+        return Err(format!("Mash {} is not jarred yet", mash_id));
+    }
+
+    let prev = get_review_state(conn, mash_id)?;
+    let (ease, interval_days, repetitions) = schedule(prev.as_ref(), grade);
+    let now = now_ms();
+    let state = ReviewState {
+        mash_id: mash_id.to_string(),
+        ease,
+        interval_days,
+        repetitions,
+        due_at: now + (interval_days * DAY_MS as f64) as i64,
+        last_reviewed_at: Some(now),
+        last_grade: Some(grade),
+    };
+    conn.execute(
+        "INSERT OR REPLACE INTO review_states
+            (mash_id, ease, interval_days, repetitions, due_at, last_reviewed_at, last_grade)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            state.mash_id,
+            state.ease,
+            state.interval_days,
+            state.repetitions,
+            state.due_at,
+            state.last_reviewed_at,
+            state.last_grade
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// JARRED mashes due at or before `now`, most overdue first. Centrality and
+/// score are left at zero; see `rank_due_reviews`.
+pub fn get_due_reviews(conn: &Connection, now: i64) -> Result<Vec<DueReview>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT m.id, m.type, m.summary, m.context, m.created_at,
+                    COALESCE(r.due_at, m.created_at + ?2), COALESCE(r.repetitions, 0),
+                    COALESCE(r.interval_days, 0),
+                    EXISTS (SELECT 1 FROM edges e WHERE e.relation_type = 'CONFLICTS_WITH'
+                            AND (e.source_id = m.id OR e.target_id = m.id))
+             FROM mashes m LEFT JOIN review_states r ON r.mash_id = m.id
+             WHERE m.status = 'JARRED' AND COALESCE(r.due_at, m.created_at + ?2) <= ?1
+             ORDER BY 6",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![now, DAY_MS], |row| {
+            Ok(DueReview {
+                id: row.get(0)?,
+                mash_type: row.get(1)?,
+                summary: row.get(2)?,
+                context: row.get(3)?,
+                created_at: row.get(4)?,
+                due_at: row.get(5)?,
+                repetitions: row.get(6)?,
+                interval_days: row.get(7)?,
+                pagerank: 0.0,
+                in_conflict: row.get(8)?,
+                score: 0.0,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Score by days overdue. With `prioritize`, weight that by PageRank relative
+/// to the average node and boost mashes caught in a conflict.
+pub fn rank_due_reviews(
+    reviews: &mut [DueReview],
+    pagerank: &HashMap<String, f64>,
+    now: i64,
+    prioritize: bool,
+) {
+    let n = pagerank.len().max(1) as f64;
+    for r in reviews.iter_mut() {
+        r.pagerank = pagerank.get(&r.id).copied().unwrap_or(0.0);
+        let overdue = 1.0 + (now - r.due_at).max(0) as f64 / DAY_MS as f64;
+        r.score = if prioritize {
+            let boost = if r.in_conflict { CONFLICT_BOOST } else { 1.0 };
+            overdue * (1.0 + r.pagerank * n) * boost
+        } else {
+            overdue
+        };
+    }
+    reviews.sort_by(|a, b| b.score.total_cmp(&a.score));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run `grades` through `schedule` from a fresh mash, returning each step.
+    fn run(grades: &[u8]) -> Vec<(f64, f64, u32)> {
+        let mut state: Option<ReviewState> = None;
+        grades
+            .iter()
+            .map(|&grade| {
+                let step = schedule(state.as_ref(), grade);
+                state = Some(ReviewState {
+                    mash_id: "m".to_string(),
+                    ease: step.0,
+                    interval_days: step.1,
+                    repetitions: step.2,
+                    due_at: 0,
+                    last_reviewed_at: None,
+                    last_grade: Some(grade),
+                });
+                step
+            })
+            .collect()
+    }
+
+    #[test]
+    fn good_grades_grow_the_interval() {
+        let steps = run(&[5, 5, 5]);
+        let intervals: Vec<f64> = steps.iter().map(|s| s.1).collect();
+        assert_eq!(intervals[..2], [1.0, 6.0]);
+        assert_eq!(intervals[2], (6.0 * steps[2].0).round());
+        assert_eq!(steps[2].2, 3);
+        assert!((steps[0].0 - 2.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ease_never_drops_below_the_floor() {
+        let steps = run(&[0; 10]);
+        assert!(steps.iter().all(|s| s.0 >= MIN_EASE));
+        assert_eq!(steps[9].0, MIN_EASE);
+    }
+
+    #[test]
+    fn a_lapse_starts_over_at_one_day() {
+        let steps = run(&[4, 4, 4, 4, 2, 4]);
+        assert!(steps[3].1 > 6.0);
+        assert_eq!((steps[4].1, steps[4].2), (1.0, 0));
+        assert!(steps[4].0 < steps[3].0);
+        // The relearned mash climbs back up from the first step
+        assert_eq!((steps[5].1, steps[5].2), (1.0, 1));
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+
+use rusqlite::{params, Connection};
+
+use crate::models::{ActivityBucket, TimelineEvent, TimelinePage};
+
+/// Events derived from the history tables: the first version of a mash is its
+/// capture, the first DISTILLED and JARRED versions mark those steps, later
+/// versions with different text are edits, and the first version of an edge is
+/// the link. Mashes that predate history count as captured and jarred when
+/// they were created.
+const EVENTS_CTE: &str = "
+    WITH versions AS (
+        SELECT mash_id, type, status, summary, context, memo, valid_from,
+               ROW_NUMBER() OVER (PARTITION BY mash_id ORDER BY valid_from, id) AS version,
+               ROW_NUMBER() OVER (PARTITION BY mash_id, status ORDER BY valid_from, id) AS status_version,
+               LAG(type) OVER (PARTITION BY mash_id ORDER BY valid_from, id) AS prev_type,
+               LAG(summary) OVER (PARTITION BY mash_id ORDER BY valid_from, id) AS prev_summary,
+               LAG(context) OVER (PARTITION BY mash_id ORDER BY valid_from, id) AS prev_context,
+               LAG(memo) OVER (PARTITION BY mash_id ORDER BY valid_from, id) AS prev_memo
+        FROM mash_history
+    ),
+    links AS (
+        SELECT edge_id, source_id, target_id, relation_type, valid_from,
+               ROW_NUMBER() OVER (PARTITION BY edge_id ORDER BY valid_from, id) AS version
+        FROM edge_history
+    ),
+    events AS (
+        SELECT 'captured' AS kind, valid_from AS at, mash_id, type AS category,
+               NULL AS edge_id, NULL AS target_id
+        FROM versions WHERE version = 1
+        UNION ALL
+        SELECT 'distilled', valid_from, mash_id, type, NULL, NULL
+        FROM versions WHERE status = 'DISTILLED' AND status_version = 1
+        UNION ALL
+        SELECT 'jarred', valid_from, mash_id, type, NULL, NULL
+        FROM versions WHERE status = 'JARRED' AND status_version = 1
+        UNION ALL
+        SELECT 'edited', valid_from, mash_id, type, NULL, NULL
+        FROM versions
+        WHERE version > 1
+          AND (type IS NOT prev_type OR summary IS NOT prev_summary
+               OR context IS NOT prev_context OR memo IS NOT prev_memo)
+        UNION ALL
+        SELECT 'linked', valid_from, source_id, relation_type, edge_id, target_id
+        FROM links WHERE version = 1
+    )";
+
+/// Counts of captured, jarred and linked events between `from` and `to`
+/// (Unix milliseconds, end exclusive), bucketed by local day or week.
+pub fn get_activity_histogram(
+    conn: &Connection,
+    from: i64,
+    to: i64,
+    bucket: &str,
+) -> Result<Vec<ActivityBucket>, String> {
+    let start_expr = match bucket {
+        "day" => "date(at / 1000, 'unixepoch', 'localtime')",
+        "week" => "date(at / 1000, 'unixepoch', 'localtime', 'weekday 0', '-6 days')",
+        other => return Err(format!("Unknown bucket size: '{}'", other)),
+    };
+    let sql = format!(
+        "{}
+         SELECT {} AS start, kind, category, COUNT(*)
+         FROM events
+         WHERE at >= ?1 AND at < ?2 AND kind IN ('captured', 'jarred', 'linked')
+         GROUP BY start, kind, category",
+        EVENTS_CTE, start_expr
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![from, to], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, i64>(3)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut buckets: BTreeMap<String, ActivityBucket> = BTreeMap::new();
+    for row in rows {
+        let (start, kind, category, count) = row.map_err(|e| e.to_string())?;
+        let entry = buckets
+            .entry(start.clone())
+            .or_insert_with(|| ActivityBucket {
+                start,
+                captured: Default::default(),
+                jarred: Default::default(),
+                linked: Default::default(),
+            });
+        let counts = match kind.as_str() {
+            "captured" => &mut entry.captured,
+            "jarred" => &mut entry.jarred,
+            _ => &mut entry.linked,
+        };
+        counts.insert(category, count);
+    }
+    Ok(buckets.into_values().collect())
+}
+
+/// Events between `from` and `to` in chronological order, one page at a time.
+/// Summaries are the latest known text, so deleted mashes still read sensibly.
+pub fn get_timeline_feed(
+    conn: &Connection,
+    from: i64,
+    to: i64,
+    offset: usize,
+    limit: usize,
+) -> Result<TimelinePage, String> {
+    let total: i64 = conn
+        .query_row(
+            &format!("{} SELECT COUNT(*) FROM events WHERE at >= ?1 AND at < ?2", EVENTS_CTE),
+            params![from, to],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let sql = format!(
+        "{}
+         SELECT ev.kind, ev.at, ev.mash_id,
+                (SELECT summary FROM mash_history WHERE mash_id = ev.mash_id
+                 ORDER BY valid_from DESC, id DESC LIMIT 1),
+                ev.category, ev.edge_id, ev.target_id,
+                (SELECT summary FROM mash_history WHERE mash_id = ev.target_id
+                 ORDER BY valid_from DESC, id DESC LIMIT 1)
+         FROM events ev
+         WHERE ev.at >= ?1 AND ev.at < ?2
+         ORDER BY ev.at, ev.kind, ev.mash_id
+         LIMIT ?3 OFFSET ?4",
+        EVENTS_CTE
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let events = stmt
+        .query_map(params![from, to, limit as i64, offset as i64], |row| {
+            Ok(TimelineEvent {
+                kind: row.get(0)?,
+                at: row.get(1)?,
+                mash_id: row.get(2)?,
+                summary: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+                category: row.get(4)?,
+                edge_id: row.get(5)?,
+                target_id: row.get(6)?,
+                target_summary: row.get(7)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(TimelinePage { events, total })
+}
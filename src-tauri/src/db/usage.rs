@@ -0,0 +1,162 @@
+use std::collections::{BTreeSet, HashMap};
+
+use rusqlite::{params, Connection};
+
+use crate::ai::usage::Usage;
+use crate::db::now_ms;
+use crate::models::{ModelPrice, UsageReport, UsageReportRow};
+
+/// List prices of the default models in USD per million tokens, as of writing.
+/// Override or extend them with the `model_prices` setting.
+const DEFAULT_PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("text-embedding-3-small", 0.02, 0.0),
+    ("text-embedding-3-large", 0.13, 0.0),
+    ("gemini-2.0-flash", 0.10, 0.40),
+    ("gemini-embedding-001", 0.15, 0.0),
+];
+
+pub fn record_usage(
+    conn: &Connection,
+    provider: &str,
+    model: &str,
+    kind: &str,
+    phase: &str,
+    run_id: Option<&str>,
+    usage: &Usage,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO api_usage (called_at, provider, model, kind, phase, run_id,
+                                prompt_tokens, completion_tokens, estimated)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            now_ms(),
+            provider,
+            model,
+            kind,
+            phase,
+            run_id,
+            usage.prompt_tokens as i64,
+            usage.completion_tokens as i64,
+            usage.estimated
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Built-in prices overlaid with the `model_prices` setting, a JSON object such
+/// as `{"gpt-4o": {"input": 2.5, "output": 10}}`.
+pub fn get_price_table(conn: &Connection) -> Result<HashMap<String, ModelPrice>, String> {
+    let mut prices: HashMap<String, ModelPrice> = DEFAULT_PRICES
+        .iter()
+        .map(|(model, input, output)| {
+            (
+                model.to_string(),
+                ModelPrice {
+                    input: *input,
+                    output: *output,
+                },
+            )
+        })
+        .collect();
+    if let Some(json) = crate::db::settings::get_setting(conn, "model_prices")? {
+        let custom: HashMap<String, ModelPrice> = serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid model_prices setting: {}", e))?;
+        prices.extend(custom);
+    }
+    Ok(prices)
+}
+
+/// Estimated cost in USD, or None when the model has no price.
+pub fn cost_of(
+    prices: &HashMap<String, ModelPrice>,
+    model: &str,
+    prompt_tokens: i64,
+    completion_tokens: i64,
+) -> Option<f64> {
+    prices.get(model).map(|p| {
+        (prompt_tokens as f64 * p.input + completion_tokens as f64 * p.output) / 1_000_000.0
+    })
+}
+
+/// Usage between `from` and `to` (Unix ms, end exclusive) grouped by local
+/// day, provider, model and phase, newest day first.
+pub fn get_usage_report(conn: &Connection, from: i64, to: i64) -> Result<UsageReport, String> {
+    let prices = get_price_table(conn)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT date(called_at / 1000, 'unixepoch', 'localtime') AS day, provider, model, phase,
+                    COUNT(*), SUM(prompt_tokens), SUM(completion_tokens), SUM(estimated)
+             FROM api_usage
+             WHERE called_at >= ?1 AND called_at < ?2
+             GROUP BY day, provider, model, phase
+             ORDER BY day DESC, provider, model, phase",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![from, to], |row| {
+            Ok(UsageReportRow {
+                day: row.get(0)?,
+                provider: row.get(1)?,
+                model: row.get(2)?,
+                phase: row.get(3)?,
+                calls: row.get(4)?,
+                prompt_tokens: row.get(5)?,
+                completion_tokens: row.get(6)?,
+                estimated_calls: row.get(7)?,
+                cost: None,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut report = UsageReport {
+        rows: Vec::with_capacity(rows.len()),
+        total_prompt_tokens: 0,
+        total_completion_tokens: 0,
+        total_cost: 0.0,
+        unpriced_models: vec![],
+    };
+    let mut unpriced = BTreeSet::new();
+    for mut row in rows {
+        row.cost = cost_of(&prices, &row.model, row.prompt_tokens, row.completion_tokens);
+        match row.cost {
+            Some(cost) => report.total_cost += cost,
+            None => {
+                unpriced.insert(row.model.clone());
+            }
+        }
+        report.total_prompt_tokens += row.prompt_tokens;
+        report.total_completion_tokens += row.completion_tokens;
+        report.rows.push(row);
+    }
+    report.unpriced_models = unpriced.into_iter().collect();
+    Ok(report)
+}
+
+/// Tokens used and estimated cost in USD since `since` (Unix ms). Calls to
+/// unpriced models count towards tokens only.
+pub fn get_spend_since(conn: &Connection, since: i64) -> Result<(i64, f64), String> {
+    let prices = get_price_table(conn)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT model, SUM(prompt_tokens), SUM(completion_tokens)
+             FROM api_usage WHERE called_at >= ?1 GROUP BY model",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![since], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?))
+        })
+        .map_err(|e| e.to_string())?;
+    let mut tokens = 0i64;
+    let mut cost = 0.0f64;
+    for row in rows {
+        let (model, prompt, completion) = row.map_err(|e| e.to_string())?;
+        tokens += prompt + completion;
+        cost += cost_of(&prices, &model, prompt, completion).unwrap_or(0.0);
+    }
+    Ok((tokens, cost))
+}
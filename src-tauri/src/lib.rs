@@ -1,4 +1,5 @@
 mod ai;
+mod analytics;
 mod commands;
 mod db;
 mod models;
@@ -99,6 +100,7 @@ pub fn run() {
 
             app.manage(DbState(conn));
             app.manage(scheduler);
+            app.manage(ai::chat::ChatStreams::default());
 
             // --- Tray icon ---
             let show = MenuItemBuilder::with_id("show", "Show Moonshine").build(app)?;
@@ -194,14 +196,58 @@ pub fn run() {
             commands::mashes::update_mash,
             commands::mashes::set_mash_status,
             commands::mashes::search_mashes,
+            commands::mashes::find_duplicates,
+            commands::mashes::dismiss_duplicate,
+            commands::mashes::merge_mashes,
+            commands::mashes::get_mash_revisions,
+            commands::mashes::preview_split,
+            commands::mashes::split_mash,
+            commands::mashes::get_mash_types,
+            commands::mashes::save_mash_type,
+            commands::mashes::delete_mash_type,
             // Graph
             commands::graph::get_graph,
+            commands::graph::get_graph_at,
+            commands::graph::diff_graph,
             commands::graph::get_node_detail,
             commands::graph::expand_node,
             commands::graph::add_edge,
             commands::graph::update_edge,
             commands::graph::delete_edge,
             commands::graph::update_node,
+            commands::graph::get_relation_types,
+            commands::graph::save_relation_type,
+            commands::graph::delete_relation_type,
+            commands::graph::get_pending_edges,
+            commands::graph::accept_edge,
+            commands::graph::reject_edge,
+            commands::graph::get_graph_metrics,
+            commands::graph::get_clusters,
+            commands::graph::refresh_clusters,
+            commands::graph::find_paths,
+            // Conflicts
+            commands::conflicts::get_conflicts_report,
+            commands::conflicts::review_conflicts,
+            // Ask
+            commands::ask::ask,
+            commands::ask::cancel_chat,
+            commands::ask::get_conversations,
+            commands::ask::get_conversation_messages,
+            commands::ask::delete_conversation,
+            // Digests
+            commands::digests::get_digests,
+            commands::digests::generate_digest_now,
+            commands::digests::export_digest,
+            // Open questions
+            commands::questions::get_open_questions,
+            commands::questions::resolve_question,
+            commands::questions::reopen_question,
+            // Reviews
+            commands::reviews::get_due_reviews,
+            commands::reviews::record_review,
+            // Timeline
+            commands::timeline::get_activity_histogram,
+            commands::timeline::get_timeline_feed,
             // Search
             commands::search::search_keyword,
             commands::search::search_semantic,
@@ -223,6 +269,12 @@ pub fn run() {
             commands::settings::switch_chat_model,
             commands::settings::reextract_relationships,
             commands::settings::reembed_all,
+            commands::settings::dry_run_reextract_relationships,
+            commands::settings::dry_run_reembed_all,
+            commands::settings::get_embedding_cache_stats,
+            commands::settings::prune_embedding_cache,
+            // Usage
+            commands::usage::get_usage_report,
             // Pipeline
             commands::pipeline::trigger_pipeline,
             commands::pipeline::set_pipeline_interval,
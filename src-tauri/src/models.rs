@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +16,83 @@ pub struct Mash {
     pub updated_at: i64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MashRevision {
+    pub id: i64,
+    pub mash_id: String,
+    /// The mash this snapshot was taken from; differs from `mash_id` for merged-away mashes.
+    pub original_id: String,
+    #[serde(rename = "type")]
+    pub mash_type: String,
+    pub summary: String,
+    pub context: String,
+    pub memo: String,
+    pub reason: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateCandidate {
+    pub source_id: String,
+    pub source_summary: String,
+    pub source_status: String,
+    pub target_id: String,
+    pub target_summary: String,
+    pub target_status: String,
+    /// None when either mash has no embedding yet.
+    pub cosine: Option<f64>,
+    /// Jaccard overlap of summary trigrams.
+    pub trigram: f64,
+}
+
+/// One atomic mash proposed by a split; editable before it is committed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitPart {
+    #[serde(rename = "type")]
+    pub mash_type: String,
+    pub summary: String,
+    pub context: String,
+    pub memo: String,
+    /// Edges of the original that should move to this part.
+    pub edge_ids: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitPreview {
+    pub original: Mash,
+    pub edges: Vec<Edge>,
+    pub parts: Vec<SplitPart>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MashTypeDef {
+    pub code: String,
+    pub labels: HashMap<String, String>,
+    pub description: String,
+    pub position: i64,
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// One of `db::mash_types::MASH_TYPE_ROLES`, if the type plays one.
+    pub role: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelationTypeDef {
+    pub code: String,
+    pub description: String,
+    pub directed: bool,
+    pub inverse_label: Option<String>,
+    pub position: i64,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Edge {
@@ -23,6 +102,12 @@ pub struct Edge {
     pub relation_type: String,
     pub source: String,
     pub confidence: f64,
+    pub rationale: Option<String>,
+    pub model: Option<String>,
+    pub extracted_at: Option<i64>,
+    pub review_state: String,
+    /// JSON describing the graph structure that suggested the edge, if any.
+    pub evidence: Option<String>,
     pub created_at: i64,
     pub updated_at: i64,
 }
@@ -36,6 +121,7 @@ pub struct GraphNode {
     pub summary: String,
     pub context: String,
     pub memo: String,
+    pub cluster_id: Option<i64>,
     pub created_at: i64,
     pub updated_at: i64,
 }
@@ -49,6 +135,11 @@ pub struct GraphEdge {
     pub relation_type: String,
     pub source: String,
     pub confidence: f64,
+    pub rationale: Option<String>,
+    pub model: Option<String>,
+    pub extracted_at: Option<i64>,
+    pub review_state: String,
+    pub evidence: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,6 +149,127 @@ pub struct GraphData {
     pub edges: Vec<GraphEdge>,
 }
 
+/// An edge whose endpoints stayed connected but under a different relation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetypedEdge {
+    pub edge: GraphEdge,
+    pub previous_relation_type: String,
+}
+
+/// What changed on the jar shelf between two points in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphDiff {
+    pub added_nodes: Vec<GraphNode>,
+    pub removed_nodes: Vec<GraphNode>,
+    pub added_edges: Vec<GraphEdge>,
+    pub removed_edges: Vec<GraphEdge>,
+    pub retyped_edges: Vec<RetypedEdge>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphPath {
+    pub node_ids: Vec<String>,
+    pub edge_ids: Vec<i64>,
+    pub cost: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathResult {
+    pub graph: GraphData,
+    pub paths: Vec<GraphPath>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EdgeReviewItem {
+    pub id: i64,
+    pub source_id: String,
+    pub source_summary: String,
+    pub target_id: String,
+    pub target_summary: String,
+    pub relation_type: String,
+    pub confidence: f64,
+    pub rationale: Option<String>,
+    pub model: Option<String>,
+    pub extracted_at: Option<i64>,
+    pub evidence: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cluster {
+    pub id: i64,
+    pub size: u32,
+    pub title: Option<String>,
+    pub summary: Option<String>,
+    pub summary_model: Option<String>,
+    pub summarized_at: Option<i64>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictResolution {
+    /// The mash that supersedes the other, or None when both still stand.
+    pub superseding_id: Option<String>,
+    pub rationale: String,
+    pub model: String,
+    pub proposed_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictItem {
+    pub edge_id: i64,
+    pub source_id: String,
+    pub source_summary: String,
+    pub source_type: String,
+    pub target_id: String,
+    pub target_summary: String,
+    pub target_type: String,
+    pub confidence: f64,
+    pub rationale: Option<String>,
+    pub edge_source: String,
+    pub review_state: String,
+    pub updated_at: i64,
+    /// Both ends are of a type with the `decision` role.
+    pub decision_conflict: bool,
+    pub resolution: Option<ConflictResolution>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictGroup {
+    /// Shared cluster of both mashes; None for conflicts across topics.
+    pub cluster_id: Option<i64>,
+    pub cluster_title: Option<String>,
+    pub conflicts: Vec<ConflictItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictsReport {
+    pub groups: Vec<ConflictGroup>,
+    pub total: u32,
+    pub unresolved_decisions: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeMetrics {
+    pub id: String,
+    pub degree: u32,
+    pub betweenness: f64,
+    pub pagerank: f64,
+    pub clustering: f64,
+    pub is_hub: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SimilarPair {
@@ -77,6 +289,9 @@ pub struct PipelineStatus {
     pub on_still_count: u32,
     pub running: bool,
     pub progress: Option<PipelineProgress>,
+    pub budget: BudgetStatus,
+    /// Phases the last run skipped because a spending cap was reached.
+    pub last_run_paused: PipelineRunResult,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -94,6 +309,7 @@ pub struct GraphFilters {
     pub mash_types: Option<Vec<String>>,
     pub relation_types: Option<Vec<String>>,
     pub sources: Option<Vec<String>>,
+    pub clusters: Option<Vec<i64>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -104,3 +320,241 @@ pub struct RecentSearch {
     pub result_count: usize,
     pub created_at: i64,
 }
+
+/// Activity counts for one day or week, keyed by mash type or relation type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityBucket {
+    /// Local date the bucket starts on, `YYYY-MM-DD`; weeks start on Monday.
+    pub start: String,
+    pub captured: HashMap<String, i64>,
+    pub jarred: HashMap<String, i64>,
+    pub linked: HashMap<String, i64>,
+}
+
+/// One entry of the chronological feed: captured, distilled, jarred, edited or linked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineEvent {
+    pub kind: String,
+    pub at: i64,
+    pub mash_id: String,
+    pub summary: String,
+    /// Mash type for mash events, relation type for links.
+    pub category: String,
+    pub edge_id: Option<i64>,
+    pub target_id: Option<String>,
+    pub target_summary: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelinePage {
+    pub events: Vec<TimelineEvent>,
+    pub total: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Conversation {
+    pub id: String,
+    pub title: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationMessage {
+    pub id: i64,
+    pub conversation_id: String,
+    /// "user" or "assistant".
+    pub role: String,
+    pub content: String,
+    /// Mash IDs the answer cites; empty for questions.
+    pub cited_ids: Vec<String>,
+    pub model: Option<String>,
+    pub created_at: i64,
+}
+
+/// An answer together with the subgraph of the mashes it cites.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AskResponse {
+    pub conversation_id: String,
+    pub message: ConversationMessage,
+    pub graph: GraphData,
+}
+
+/// Payload of the `chat-chunk` event: one streamed piece of a chat answer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatChunk {
+    pub request_id: String,
+    pub delta: String,
+}
+
+/// A periodic Markdown report on what changed on the jar shelf.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Digest {
+    pub id: i64,
+    pub period_start: i64,
+    pub period_end: i64,
+    pub markdown: String,
+    pub model: Option<String>,
+    pub created_at: i64,
+}
+
+
+/// A pending RESOLVED_BY proposal for an open question.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolutionCandidate {
+    pub edge_id: i64,
+    pub mash_id: String,
+    pub summary: String,
+    pub confidence: f64,
+    pub rationale: Option<String>,
+}
+
+/// A JARRED question or problem that nothing has resolved yet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenQuestion {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub mash_type: String,
+    pub summary: String,
+    pub created_at: i64,
+    pub age_days: f64,
+    pub pagerank: f64,
+    /// Age weighted by centrality; the list is ordered by it, highest first.
+    pub score: f64,
+    pub candidates: Vec<ResolutionCandidate>,
+}
+
+/// SM-2 scheduling state of a reviewed mash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewState {
+    pub mash_id: String,
+    pub ease: f64,
+    pub interval_days: f64,
+    pub repetitions: u32,
+    pub due_at: i64,
+    pub last_reviewed_at: Option<i64>,
+    pub last_grade: Option<u8>,
+}
+
+/// A JARRED mash due for review. Never-reviewed mashes fall due a day after capture.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DueReview {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub mash_type: String,
+    pub summary: String,
+    pub context: String,
+    pub created_at: i64,
+    pub due_at: i64,
+    pub repetitions: u32,
+    pub interval_days: f64,
+    pub pagerank: f64,
+    pub in_conflict: bool,
+    /// Queue order, highest first: overdue time, boosted by centrality and
+    /// conflicts when prioritizing.
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddingCacheStats {
+    pub entries: i64,
+    pub bytes: i64,
+}
+
+/// USD per million tokens.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPrice {
+    pub input: f64,
+    #[serde(default)]
+    pub output: f64,
+}
+
+/// Provider calls aggregated by local day, model and phase.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReportRow {
+    pub day: String,
+    pub provider: String,
+    pub model: String,
+    pub phase: String,
+    pub calls: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    /// Calls whose token counts were estimated from text length.
+    pub estimated_calls: i64,
+    /// None when the model has no price.
+    pub cost: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReport {
+    pub rows: Vec<UsageReportRow>,
+    pub total_prompt_tokens: i64,
+    pub total_completion_tokens: i64,
+    pub total_cost: f64,
+    pub unpriced_models: Vec<String>,
+}
+
+/// Spend so far today and this month (local time) against the configured caps.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetStatus {
+    pub daily_tokens: i64,
+    pub daily_cost: f64,
+    pub monthly_tokens: i64,
+    pub monthly_cost: f64,
+    pub daily_token_cap: Option<i64>,
+    pub daily_cost_cap: Option<f64>,
+    pub monthly_token_cap: Option<i64>,
+    pub monthly_cost_cap: Option<f64>,
+    /// Set when a cap is reached, e.g. "Daily cost cap reached ($1.02 of $1.00)".
+    pub exceeded: Option<String>,
+}
+
+/// What a pipeline run skipped because a spending cap was reached.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineRunResult {
+    pub paused_phases: Vec<String>,
+    pub paused_reason: Option<String>,
+}
+
+/// What `reembed_all` or `reextract_relationships` would do, computed without
+/// calling a provider or touching the database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunReport {
+    pub operation: String,
+    pub mash_count: u32,
+    /// Texts to embed, of which `cached_embeddings` are already in the cache.
+    pub embedding_texts: u32,
+    pub cached_embeddings: u32,
+    pub embedding_calls: u32,
+    /// Mashes whose relationships would be extracted again.
+    pub reextract_count: u32,
+    pub similar_pairs: u32,
+    pub chat_batches: u32,
+    pub embedding_tokens: i64,
+    pub chat_prompt_tokens: i64,
+    pub chat_completion_tokens: i64,
+    /// None when a model involved has no price.
+    pub estimated_cost: Option<f64>,
+    pub edges_to_delete_count: u32,
+    /// The first pending AI edges that would be deleted, most confident first.
+    pub edges_to_delete: Vec<EdgeReviewItem>,
+}
@@ -4,16 +4,32 @@ use std::sync::{Arc, Mutex};
 use rusqlite::{params, Connection};
 
 use crate::ai::embedding::EmbeddingConfig;
-use crate::ai::relationship::{extract_relations, RelationCandidate};
+use crate::ai::relationship::{estimate_batch_usage, extract_batch, RelationCandidate, BATCH_SIZE};
+use crate::analytics::link_prediction::{predict_links, PredictedLink};
+use crate::analytics::load_graph;
+use crate::db::edge_review::{get_rejected_pairs, is_pair_rejected};
+use crate::db::edges::upsert_ai_edge;
 use crate::db::now_ms;
+use crate::models::PipelineRunResult;
+use crate::pipeline::budget::pause_if_over_budget;
 use crate::similarity::{deserialize_embedding, find_similar_mashes};
 
+/// How many structurally implied pairs each backfill run sends to the model.
+const STRUCTURAL_CANDIDATES_PER_RUN: usize = 20;
+
+type PairSet = HashSet<(String, String)>;
+type PairScores = HashMap<(String, String), f64>;
+
+/// Propose edges for isolated JARRED mashes (by embedding similarity) and for
+/// pairs the existing graph implies (by link prediction). Stops between batches
+/// once a spending cap would be exceeded.
 pub async fn backfill_isolated_nodes(
     conn: &Arc<Mutex<Connection>>,
     config: &EmbeddingConfig,
+    result: &mut PipelineRunResult,
 ) -> Result<u32, String> {
-    // Read similarity settings
-    let (threshold, pipeline_top_k) = {
+    // Read similarity settings and the relation vocabulary
+    let (threshold, pipeline_top_k, relation_types) = {
         let conn = conn.lock().map_err(|e| e.to_string())?;
         let threshold = crate::db::settings::get_setting(&conn, "pipeline_threshold")?
             .and_then(|v| v.parse::<f32>().ok())
@@ -21,120 +37,264 @@ pub async fn backfill_isolated_nodes(
         let top_k = crate::db::settings::get_setting(&conn, "pipeline_top_k")?
             .and_then(|v| v.parse::<usize>().ok())
             .unwrap_or(5);
-        (threshold, top_k)
+        let relation_types = crate::db::relation_types::get_relation_types(&conn)?;
+        (threshold, top_k, relation_types)
     };
 
-    // Step 1: Find isolated JARRED nodes (sync)
-    let (isolated, candidates) = {
+    // Step 1: Find isolated JARRED nodes and their similar pairs (sync)
+    let (isolated_count, mut candidates) = {
         let conn = conn.lock().map_err(|e| e.to_string())?;
+        let isolated = find_isolated(&conn)?;
+        let candidates = similarity_candidates(&conn, &isolated, pipeline_top_k, threshold)?;
+        (isolated.len(), candidates)
+    };
 
-        let mut stmt = conn
-            .prepare(
-                "SELECT m.id, m.summary, m.embedding
-                 FROM mashes m
-                 WHERE m.status = 'JARRED'
-                   AND m.embedding IS NOT NULL
-                   AND m.id NOT IN (SELECT source_id FROM edges)
-                   AND m.id NOT IN (SELECT target_id FROM edges)",
-            )
-            .map_err(|e| e.to_string())?;
-
-        let isolated: Vec<(String, String, Vec<f32>)> = stmt
-            .query_map([], |row| {
-                let blob: Vec<u8> = row.get(2)?;
-                Ok((row.get(0)?, row.get(1)?, deserialize_embedding(&blob)))
-            })
-            .map_err(|e| e.to_string())?
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| e.to_string())?;
+    // Step 2: Pairs implied by the graph structure (sync; lock released while scoring)
+    let (graph, supports, skip) = {
+        let conn = conn.lock().map_err(|e| e.to_string())?;
+        let (supports, skip) = load_link_prediction_inputs(&conn)?;
+        (load_graph(&conn)?, supports, skip)
+    };
+    let links = predict_links(&graph, &supports, &skip, STRUCTURAL_CANDIDATES_PER_RUN);
+    let scores = {
+        let conn = conn.lock().map_err(|e| e.to_string())?;
+        let (structural, scores) = structural_candidates(&conn, links)?;
+        log::info!("Backfill: {} structural candidates", structural.len());
+        candidates.extend(structural);
+        scores
+    };
+
+    if candidates.is_empty() {
+        log::info!("Backfill: no candidates");
+        return Ok(0);
+    }
 
-        if isolated.is_empty() {
-            log::info!("Backfill: no isolated nodes");
-            return Ok(0);
+    // Step 3: AI relationship extraction per batch (async), then create edges and
+    // record structural attempts (sync). A failed batch leaves its pairs to be
+    // proposed again on the next run.
+    let mut edges_created = 0u32;
+    for batch in candidates.chunks(BATCH_SIZE) {
+        let estimate = estimate_batch_usage(&relation_types, batch)?;
+        if pause_if_over_budget(conn, "backfill", Some((&config.chat_model, &estimate)), result) {
+            break;
         }
 
-        log::info!("Backfill: found {} isolated nodes", isolated.len());
+        let relations = match extract_batch(config, &relation_types, batch).await {
+            Ok(r) => r,
+            Err(e) => {
+                log::warn!("Backfill: extraction batch failed, skipping: {}", e);
+                continue;
+            }
+        };
 
-        // Find similar pairs
-        let mut all_pairs = Vec::new();
-        for (id, _, embedding) in &isolated {
-            match find_similar_mashes(&conn, id, embedding, pipeline_top_k, threshold) {
-                Ok(pairs) => all_pairs.extend(pairs),
-                Err(e) => log::warn!("Backfill similarity search failed for {}: {}", id, e),
+        let conn = conn.lock().map_err(|e| e.to_string())?;
+        record_link_prediction_attempts(&conn, batch, &scores)?;
+        for rel in &relations {
+            match upsert_ai_edge(&conn, &relation_types, rel, &config.chat_model) {
+                Ok(true) => edges_created += 1,
+                Ok(false) => {}
+                Err(e) => log::warn!("Backfill: failed to create edge: {}", e),
             }
         }
+    }
+
+    log::info!(
+        "Backfill complete: {} isolated, {} edges created",
+        isolated_count,
+        edges_created
+    );
+    Ok(edges_created)
+}
+
+struct IsolatedMash {
+    id: String,
+    summary: String,
+    embedding: Vec<f32>,
+}
+
+fn find_isolated(conn: &Connection) -> Result<Vec<IsolatedMash>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT m.id, m.summary, m.embedding
+             FROM mashes m
+             WHERE m.status = 'JARRED'
+               AND m.embedding IS NOT NULL
+               AND m.id NOT IN (SELECT source_id FROM edges)
+               AND m.id NOT IN (SELECT target_id FROM edges)",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let isolated: Vec<IsolatedMash> = stmt
+        .query_map([], |row| {
+            let blob: Vec<u8> = row.get(2)?;
+            Ok(IsolatedMash {
+                id: row.get(0)?,
+                summary: row.get(1)?,
+                embedding: deserialize_embedding(&blob),
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    log::info!("Backfill: found {} isolated nodes", isolated.len());
+    Ok(isolated)
+}
 
-        if all_pairs.is_empty() {
-            log::info!("Backfill: no similar pairs found");
-            return Ok(0);
+fn similarity_candidates(
+    conn: &Connection,
+    isolated: &[IsolatedMash],
+    top_k: usize,
+    threshold: f32,
+) -> Result<Vec<RelationCandidate>, String> {
+    // Find similar pairs
+    let mut all_pairs = Vec::new();
+    for mash in isolated {
+        match find_similar_mashes(conn, &mash.id, &mash.embedding, top_k, threshold) {
+            Ok(pairs) => all_pairs.extend(pairs),
+            Err(e) => log::warn!("Backfill similarity search failed for {}: {}", mash.id, e),
         }
+    }
 
-        // Build candidates
-        let mut summary_map: HashMap<String, String> = isolated
-            .iter()
-            .map(|(id, summary, _)| (id.clone(), summary.clone()))
-            .collect();
-
-        let missing_ids: Vec<String> = all_pairs
-            .iter()
-            .map(|p| p.target_id.clone())
-            .filter(|id| !summary_map.contains_key(id))
-            .collect::<HashSet<_>>()
-            .into_iter()
-            .collect();
-
-        for id in &missing_ids {
-            let mut stmt = conn
-                .prepare("SELECT summary FROM mashes WHERE id = ?1")
-                .map_err(|e| e.to_string())?;
-            if let Ok(summary) = stmt.query_row(params![id], |row| row.get::<_, String>(0)) {
-                summary_map.insert(id.clone(), summary);
-            }
+    if all_pairs.is_empty() {
+        return Ok(vec![]);
+    }
+
+    // Build candidates
+    let mut summary_map: HashMap<String, String> = isolated
+        .iter()
+        .map(|m| (m.id.clone(), m.summary.clone()))
+        .collect();
+
+    let missing_ids: Vec<String> = all_pairs
+        .iter()
+        .map(|p| p.target_id.clone())
+        .filter(|id| !summary_map.contains_key(id))
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    fill_summaries(conn, &missing_ids, &mut summary_map)?;
+
+    let rejected = get_rejected_pairs(conn)?;
+    Ok(all_pairs
+        .iter()
+        .filter(|p| {
+            summary_map.contains_key(&p.source_id)
+                && summary_map.contains_key(&p.target_id)
+                && !is_pair_rejected(&rejected, &p.source_id, &p.target_id)
+        })
+        .map(|p| RelationCandidate {
+            source_id: p.source_id.clone(),
+            source_summary: summary_map[&p.source_id].clone(),
+            target_id: p.target_id.clone(),
+            target_summary: summary_map[&p.target_id].clone(),
+            evidence: None,
+        })
+        .collect())
+}
+
+fn fill_summaries(
+    conn: &Connection,
+    ids: &[String],
+    summary_map: &mut HashMap<String, String>,
+) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare("SELECT summary FROM mashes WHERE id = ?1")
+        .map_err(|e| e.to_string())?;
+    for id in ids {
+        if let Ok(summary) = stmt.query_row(params![id], |row| row.get::<_, String>(0)) {
+            summary_map.insert(id.clone(), summary);
         }
+    }
+    Ok(())
+}
 
-        let candidates: Vec<RelationCandidate> = all_pairs
-            .iter()
-            .filter(|p| {
-                summary_map.contains_key(&p.source_id) && summary_map.contains_key(&p.target_id)
-            })
-            .map(|p| RelationCandidate {
-                source_id: p.source_id.clone(),
-                source_summary: summary_map[&p.source_id].clone(),
-                target_id: p.target_id.clone(),
-                target_summary: summary_map[&p.target_id].clone(),
-            })
-            .collect();
+/// SUPPORTS edges for chain detection, and pairs link prediction must skip:
+/// those already proposed once and those a person rejected.
+fn load_link_prediction_inputs(
+    conn: &Connection,
+) -> Result<(PairSet, PairSet), String> {
+    let mut stmt = conn
+        .prepare("SELECT source_id, target_id FROM edges WHERE relation_type = 'SUPPORTS'")
+        .map_err(|e| e.to_string())?;
+    let supports: PairSet = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<HashSet<_>, _>>()
+        .map_err(|e| e.to_string())?;
 
-        (isolated, candidates)
-    };
+    let mut stmt = conn
+        .prepare("SELECT source_id, target_id FROM link_prediction_attempts")
+        .map_err(|e| e.to_string())?;
+    let mut skip: PairSet = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<HashSet<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    skip.extend(get_rejected_pairs(conn)?);
+    Ok((supports, skip))
+}
 
-    // Step 2: AI relationship extraction (async)
-    let relations = extract_relations(config, candidates).await?;
+/// Turn predicted links into candidates, with each pair's link prediction score.
+fn structural_candidates(
+    conn: &Connection,
+    links: Vec<PredictedLink>,
+) -> Result<(Vec<RelationCandidate>, PairScores), String> {
+    if links.is_empty() {
+        return Ok((vec![], PairScores::new()));
+    }
 
-    // Step 3: Create edges (sync)
-    let conn = conn.lock().map_err(|e| e.to_string())?;
-    let now = now_ms();
-    let mut edges_created = 0u32;
-    for rel in &relations {
-        let result = conn.execute(
-            "INSERT INTO edges (source_id, target_id, relation_type, source, confidence, created_at, updated_at)
-             VALUES (?1, ?2, ?3, 'ai', ?4, ?5, ?6)
-             ON CONFLICT(source_id, target_id) DO UPDATE SET
-                relation_type = excluded.relation_type,
-                confidence = excluded.confidence,
-                updated_at = excluded.updated_at",
-            params![rel.source_id, rel.target_id, rel.relation, rel.confidence, now, now],
+    let ids: Vec<String> = links
+        .iter()
+        .flat_map(|l| [l.source_id.clone(), l.target_id.clone()])
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let mut summary_map = HashMap::new();
+    fill_summaries(conn, &ids, &mut summary_map)?;
+
+    let mut candidates = Vec::with_capacity(links.len());
+    let mut scores = PairScores::new();
+    for link in links {
+        let (Some(source_summary), Some(target_summary)) =
+            (summary_map.get(&link.source_id), summary_map.get(&link.target_id))
+        else {
+            continue;
+        };
+        scores.insert(
+            (link.source_id.clone(), link.target_id.clone()),
+            link.evidence.score,
         );
-        match result {
-            Ok(_) => edges_created += 1,
-            Err(e) => log::warn!("Backfill: failed to create edge: {}", e),
-        }
+        candidates.push(RelationCandidate {
+            source_summary: source_summary.clone(),
+            target_summary: target_summary.clone(),
+            evidence: serde_json::to_string(&link.evidence).ok(),
+            source_id: link.source_id,
+            target_id: link.target_id,
+        });
     }
+    Ok((candidates, scores))
+}
 
-    log::info!(
-        "Backfill complete: {} isolated, {} edges created",
-        isolated.len(),
-        edges_created
-    );
-    Ok(edges_created)
+/// Remember the structural pairs of a batch the model answered, so pairs it
+/// found unrelated are not resent on the next run.
+fn record_link_prediction_attempts(
+    conn: &Connection,
+    batch: &[RelationCandidate],
+    scores: &PairScores,
+) -> Result<(), String> {
+    let now = now_ms();
+    for candidate in batch {
+        let key = (candidate.source_id.clone(), candidate.target_id.clone());
+        let Some(score) = scores.get(&key) else {
+            continue;
+        };
+        conn.execute(
+            "INSERT OR REPLACE INTO link_prediction_attempts (source_id, target_id, score, attempted_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![candidate.source_id, candidate.target_id, score, now],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
 }
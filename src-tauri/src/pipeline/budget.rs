@@ -0,0 +1,135 @@
+use std::sync::{Arc, Mutex};
+
+use rusqlite::Connection;
+
+use crate::ai::usage::Usage;
+use crate::db;
+use crate::models::{BudgetStatus, PipelineRunResult};
+
+fn cap_setting<T: std::str::FromStr + PartialOrd + Default>(
+    conn: &Connection,
+    key: &str,
+) -> Result<Option<T>, String> {
+    Ok(db::settings::get_setting(conn, key)?
+        .and_then(|v| v.trim().parse::<T>().ok())
+        .filter(|v| *v > T::default()))
+}
+
+/// Start of the current local day or month, in Unix ms.
+fn period_start(conn: &Connection, modifier: &str) -> Result<i64, String> {
+    conn.query_row(
+        "SELECT CAST(strftime('%s', 'now', 'localtime', ?1, 'utc') AS INTEGER) * 1000",
+        [modifier],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Spend today and this month against the `budget_daily_tokens`,
+/// `budget_daily_cost`, `budget_monthly_tokens` and `budget_monthly_cost`
+/// settings. Unset or non-positive caps are ignored.
+pub fn get_budget_status(conn: &Connection) -> Result<BudgetStatus, String> {
+    let (daily_tokens, daily_cost) = db::usage::get_spend_since(conn, period_start(conn, "start of day")?)?;
+    let (monthly_tokens, monthly_cost) =
+        db::usage::get_spend_since(conn, period_start(conn, "start of month")?)?;
+    let mut status = BudgetStatus {
+        daily_tokens,
+        daily_cost,
+        monthly_tokens,
+        monthly_cost,
+        daily_token_cap: cap_setting(conn, "budget_daily_tokens")?,
+        daily_cost_cap: cap_setting(conn, "budget_daily_cost")?,
+        monthly_token_cap: cap_setting(conn, "budget_monthly_tokens")?,
+        monthly_cost_cap: cap_setting(conn, "budget_monthly_cost")?,
+        exceeded: None,
+    };
+
+    status.exceeded = match (status.daily_token_cap, status.daily_cost_cap) {
+        (Some(cap), _) if daily_tokens >= cap => Some(format!(
+            "Daily token cap reached ({} of {} tokens)",
+            daily_tokens, cap
+        )),
+        (_, Some(cap)) if daily_cost >= cap => Some(format!(
+            "Daily cost cap reached (${:.2} of ${:.2})",
+            daily_cost, cap
+        )),
+        _ => None,
+    }
+    .or(match (status.monthly_token_cap, status.monthly_cost_cap) {
+        (Some(cap), _) if monthly_tokens >= cap => Some(format!(
+            "Monthly token cap reached ({} of {} tokens)",
+            monthly_tokens, cap
+        )),
+        (_, Some(cap)) if monthly_cost >= cap => Some(format!(
+            "Monthly cost cap reached (${:.2} of ${:.2})",
+            monthly_cost, cap
+        )),
+        _ => None,
+    });
+    Ok(status)
+}
+
+/// Why a call of about `estimate` tokens on `model` must not be made: a cap
+/// already reached, or one the call would push past. None when every cap has room.
+pub fn would_exceed(conn: &Connection, model: &str, estimate: &Usage) -> Result<Option<String>, String> {
+    let status = get_budget_status(conn)?;
+    if status.exceeded.is_some() {
+        return Ok(status.exceeded);
+    }
+    let (prompt, completion) = (estimate.prompt_tokens as i64, estimate.completion_tokens as i64);
+    let tokens = prompt + completion;
+    let cost = db::usage::cost_of(&db::usage::get_price_table(conn)?, model, prompt, completion)
+        .unwrap_or(0.0);
+
+    let token_cap = |period: &str, spent: i64, cap: Option<i64>| {
+        cap.filter(|&cap| spent + tokens > cap).map(|cap| {
+            format!(
+                "{} token cap would be exceeded ({} + ~{} of {} tokens)",
+                period, spent, tokens, cap
+            )
+        })
+    };
+    let cost_cap = |period: &str, spent: f64, cap: Option<f64>| {
+        cap.filter(|&cap| spent + cost > cap).map(|cap| {
+            format!(
+                "{} cost cap would be exceeded (${:.2} + ~${:.2} of ${:.2})",
+                period, spent, cost, cap
+            )
+        })
+    };
+    Ok(token_cap("Daily", status.daily_tokens, status.daily_token_cap)
+        .or_else(|| cost_cap("Daily", status.daily_cost, status.daily_cost_cap))
+        .or_else(|| token_cap("Monthly", status.monthly_tokens, status.monthly_token_cap))
+        .or_else(|| cost_cap("Monthly", status.monthly_cost, status.monthly_cost_cap)))
+}
+
+/// Check the spending caps before a capped phase, or before one of its calls
+/// when `next_call` gives the model and estimated usage. Returns true, and
+/// records why in `result`, when the phase must stop. A failed check never
+/// blocks the run.
+pub fn pause_if_over_budget(
+    conn: &Arc<Mutex<Connection>>,
+    phase: &str,
+    next_call: Option<(&str, &Usage)>,
+    result: &mut PipelineRunResult,
+) -> bool {
+    let exceeded = conn.lock().map_err(|e| e.to_string()).and_then(|c| match next_call {
+        Some((model, estimate)) => would_exceed(&c, model, estimate),
+        None => get_budget_status(&c).map(|status| status.exceeded),
+    });
+    match exceeded {
+        Ok(Some(reason)) => {
+            log::warn!("Pipeline: pausing {}: {}", phase, reason);
+            if !result.paused_phases.iter().any(|p| p == phase) {
+                result.paused_phases.push(phase.to_string());
+            }
+            result.paused_reason.get_or_insert(reason);
+            true
+        }
+        Ok(None) => false,
+        Err(e) => {
+            log::warn!("Pipeline: budget check failed: {}", e);
+            false
+        }
+    }
+}
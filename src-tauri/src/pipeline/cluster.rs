@@ -0,0 +1,92 @@
+use std::sync::{Arc, Mutex};
+
+use rusqlite::Connection;
+
+use crate::ai::cluster_summary::{estimate_summary_usage, summarize_cluster};
+use crate::ai::embedding::EmbeddingConfig;
+use crate::analytics::{communities::detect_communities, load_graph};
+use crate::db::clusters::{get_stale_clusters, save_cluster_summary, save_clusters};
+use crate::models::{Cluster, PipelineProgress, PipelineRunResult};
+use crate::pipeline::budget::pause_if_over_budget;
+
+fn update_progress(
+    progress: &Arc<Mutex<Option<PipelineProgress>>>,
+    phase: &str,
+    step: &str,
+    current: u32,
+    total: u32,
+) {
+    if let Ok(mut p) = progress.lock() {
+        *p = Some(PipelineProgress {
+            phase: phase.to_string(),
+            step: step.to_string(),
+            current,
+            total,
+        });
+    }
+}
+
+/// Recompute topic clusters over the JARRED graph and store them with stable IDs.
+/// The lock is released while Louvain runs.
+pub fn refresh_clusters(conn: &Arc<Mutex<Connection>>) -> Result<Vec<Cluster>, String> {
+    // Load the graph (sync, locked)
+    let graph = {
+        let conn = conn.lock().map_err(|e| e.to_string())?;
+        load_graph(&conn)?
+    };
+
+    // Detect communities (unlocked)
+    let communities = detect_communities(&graph);
+
+    // Match against previous clusters and save (sync, locked)
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    save_clusters(&conn, &communities)
+}
+
+/// Title and summarize clusters whose cached summary is missing or whose
+/// membership changed materially. Failed clusters, and those left when a
+/// spending cap would be exceeded, are retried next run.
+pub async fn summarize_clusters(
+    conn: &Arc<Mutex<Connection>>,
+    config: &EmbeddingConfig,
+    progress: &Arc<Mutex<Option<PipelineProgress>>>,
+    result: &mut PipelineRunResult,
+) -> Result<u32, String> {
+    let stale = {
+        let conn = conn.lock().map_err(|e| e.to_string())?;
+        get_stale_clusters(&conn)?
+    };
+    if stale.is_empty() {
+        return Ok(0);
+    }
+
+    let total = stale.len() as u32;
+    log::info!("Summarizing {} topic clusters", total);
+
+    let mut summarized = 0u32;
+    for (i, cluster) in stale.iter().enumerate() {
+        update_progress(progress, "cluster_summary", "api", i as u32, total);
+        let estimate = estimate_summary_usage(&cluster.member_summaries);
+        if pause_if_over_budget(conn, "cluster_summary", Some((&config.chat_model, &estimate)), result) {
+            break;
+        }
+        match summarize_cluster(config, &cluster.member_summaries).await {
+            Ok(summary) => {
+                let conn = conn.lock().map_err(|e| e.to_string())?;
+                save_cluster_summary(
+                    &conn,
+                    cluster.id,
+                    &summary.title,
+                    &summary.summary,
+                    &cluster.member_ids,
+                    &config.chat_model,
+                )?;
+                summarized += 1;
+            }
+            Err(e) => log::warn!("Cluster {} summary failed, skipping: {}", cluster.id, e),
+        }
+    }
+    update_progress(progress, "cluster_summary", "api", total, total);
+
+    Ok(summarized)
+}
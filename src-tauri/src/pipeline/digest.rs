@@ -0,0 +1,101 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use rusqlite::Connection;
+
+use crate::ai::digest::write_digest;
+use crate::ai::embedding::EmbeddingConfig;
+use crate::analytics::{load_graph, metrics::compute_pagerank};
+use crate::db;
+use crate::db::digests::DigestInput;
+use crate::models::Digest;
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// How many new mashes the digest calls out as key ideas.
+const CENTRAL_IDEAS: usize = 5;
+
+const MAX_OPEN_QUESTIONS: usize = 20;
+
+/// Length of a digest period in days, from the `digest_window_days` setting.
+pub fn window_days(conn: &Connection) -> Result<i64, String> {
+    Ok(db::settings::get_setting(conn, "digest_window_days")?
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|&d| d > 0)
+        .unwrap_or(7))
+}
+
+/// Write and store the digest for [period_start, period_end). Centrality is
+/// computed without holding the lock; a quiet period gets a short note instead
+/// of a chat call.
+pub async fn generate_digest(
+    conn: &Arc<Mutex<Connection>>,
+    config: &EmbeddingConfig,
+    period_start: i64,
+    period_end: i64,
+) -> Result<Digest, String> {
+    let (mut input, graph) = {
+        let conn = conn.lock().map_err(|e| e.to_string())?;
+        let input = DigestInput {
+            period_start: db::digests::format_date(&conn, period_start)?,
+            period_end: db::digests::format_date(&conn, period_end)?,
+            new_mashes: db::digests::get_newly_jarred(&conn, period_start, period_end)?,
+            central: vec![],
+            conflicts: db::digests::get_new_conflicts(&conn, period_start, period_end)?,
+            open_questions: db::digests::get_unsupported_questions(&conn, MAX_OPEN_QUESTIONS)?,
+        };
+        (input, load_graph(&conn)?)
+    };
+
+    let new_ids: HashSet<&str> = input.new_mashes.iter().map(|m| m.id.as_str()).collect();
+    let mut ranked: Vec<(String, f64)> = compute_pagerank(&graph)
+        .into_iter()
+        .filter(|(id, _)| new_ids.contains(id.as_str()))
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let central_ids: Vec<String> = ranked
+        .into_iter()
+        .take(CENTRAL_IDEAS)
+        .map(|(id, _)| id)
+        .collect();
+    for id in &central_ids {
+        if let Some(pos) = input.new_mashes.iter().position(|m| &m.id == id) {
+            input.central.push(input.new_mashes.remove(pos));
+        }
+    }
+
+    let quiet = input.new_mashes.is_empty() && input.central.is_empty() && input.conflicts.is_empty();
+    let (markdown, model) = if quiet {
+        (
+            format!(
+                "# {} – {}\n\nNothing new was jarred in this period.",
+                input.period_start, input.period_end
+            ),
+            None,
+        )
+    } else {
+        (write_digest(config, &input).await?, Some(config.chat_model.as_str()))
+    };
+
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    db::digests::save_digest(&conn, period_start, period_end, &markdown, model)
+}
+
+/// Write the next digest once a full window has passed since the last one.
+/// The first digest covers the window ending now.
+pub async fn generate_due_digest(
+    conn: &Arc<Mutex<Connection>>,
+    config: &EmbeddingConfig,
+) -> Result<Option<Digest>, String> {
+    let (window, last_end) = {
+        let conn = conn.lock().map_err(|e| e.to_string())?;
+        (window_days(&conn)? * DAY_MS, db::digests::get_last_period_end(&conn)?)
+    };
+    let now = db::now_ms();
+    let period_start = match last_end {
+        Some(end) if now - end < window => return Ok(None),
+        Some(end) => end,
+        None => now - window,
+    };
+    generate_digest(conn, config, period_start, now).await.map(Some)
+}
@@ -3,9 +3,10 @@ use std::sync::{Arc, Mutex};
 use rusqlite::{params, Connection};
 
 use crate::ai::embedding::{generate_embeddings, EmbeddingConfig, EmbeddingTaskType};
+use crate::db::embedding_cache::{get_cached_embeddings, save_cached_embeddings};
 use crate::db::now_ms;
 use crate::models::PipelineProgress;
-use crate::similarity::serialize_embedding;
+use crate::similarity::{embedding_text, hash_text, serialize_embedding};
 
 fn update_progress(
     progress: &Arc<Mutex<Option<PipelineProgress>>>,
@@ -24,6 +25,47 @@ fn update_progress(
     }
 }
 
+/// Embed `texts` as documents, reusing cached vectors for text already embedded
+/// with the same provider, model and dimensions. Only misses reach the API.
+async fn embed_documents(
+    conn: &Arc<Mutex<Connection>>,
+    config: &EmbeddingConfig,
+    texts: Vec<String>,
+    hashes: &[String],
+) -> Result<Vec<Option<Vec<f32>>>, String> {
+    let cached = {
+        let conn = conn.lock().map_err(|e| e.to_string())?;
+        get_cached_embeddings(&conn, config, hashes)?
+    };
+    let misses: Vec<usize> = (0..texts.len())
+        .filter(|&i| !cached.contains_key(&hashes[i]))
+        .collect();
+    log::info!(
+        "Embedding cache: {} hits, {} misses",
+        texts.len() - misses.len(),
+        misses.len()
+    );
+
+    let mut results: Vec<Option<Vec<f32>>> = hashes.iter().map(|h| cached.get(h).cloned()).collect();
+    if misses.is_empty() {
+        return Ok(results);
+    }
+
+    let miss_texts: Vec<String> = misses.iter().map(|&i| texts[i].clone()).collect();
+    let fresh = generate_embeddings(config, miss_texts, EmbeddingTaskType::Document).await?;
+
+    let mut to_cache = Vec::new();
+    for (&i, embedding) in misses.iter().zip(fresh) {
+        if let Some(embedding) = embedding {
+            to_cache.push((hashes[i].clone(), embedding.clone()));
+            results[i] = Some(embedding);
+        }
+    }
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    save_cached_embeddings(&conn, config, &to_cache)?;
+    Ok(results)
+}
+
 pub async fn distill_mashes(
     conn: &Arc<Mutex<Connection>>,
     config: &EmbeddingConfig,
@@ -52,33 +94,30 @@ pub async fn distill_mashes(
     // Step 2: Generate embeddings (async) — combine summary + context + memo
     let texts: Vec<String> = mashes
         .iter()
-        .map(|(_, summary, context, memo)| {
-            let mut parts = vec![summary.as_str()];
-            if !context.is_empty() {
-                parts.push(context.as_str());
-            }
-            if !memo.is_empty() {
-                parts.push(memo.as_str());
-            }
-            parts.join("\n")
-        })
+        .map(|(_, summary, context, memo)| embedding_text(summary, context, memo))
         .collect();
-    let embeddings = generate_embeddings(config, texts, EmbeddingTaskType::Document).await?;
+    let hashes: Vec<String> = texts.iter().map(|t| hash_text(t)).collect();
+    let embeddings = embed_documents(conn, config, texts, &hashes).await?;
 
     // Step 3: Write results back (sync)
     let conn = conn.lock().map_err(|e| e.to_string())?;
     let now = now_ms();
     let mut distilled = 0u32;
 
-    for (i, (id, _, _, _)) in mashes.iter().enumerate() {
+    for (i, (id, summary, context, memo)) in mashes.iter().enumerate() {
         if let Some(Some(ref embedding)) = embeddings.get(i) {
             let blob = serialize_embedding(embedding);
-            conn.execute(
-                "UPDATE mashes SET status = 'DISTILLED', embedding = ?1, updated_at = ?2 WHERE id = ?3",
-                params![blob, now, id],
-            )
-            .map_err(|e| e.to_string())?;
-            distilled += 1;
+            // A mash edited while the API call ran keeps ON_STILL for the next run
+            let updated = conn
+                .execute(
+                    "UPDATE mashes SET status = 'DISTILLED', embedding = ?1, content_hash = ?2,
+                                       updated_at = ?3
+                     WHERE id = ?4 AND status = 'ON_STILL'
+                       AND summary = ?5 AND context = ?6 AND memo = ?7",
+                    params![blob, hashes[i], now, id, summary, context, memo],
+                )
+                .map_err(|e| e.to_string())?;
+            distilled += updated as u32;
         }
     }
 
@@ -86,19 +125,30 @@ pub async fn distill_mashes(
     Ok(distilled)
 }
 
-/// RE_EMBED: regenerate embeddings only, then go directly to JARRED (skip relationship extraction).
+/// id, summary, context, memo, content hash and the status it was requeued from.
+type ReembedRow = (String, String, String, String, Option<String>, Option<String>);
+
+/// RE_EMBED: regenerate embeddings, then go back to the status the mash was
+/// requeued from (JARRED by default) when the text is the same one last
+/// embedded (e.g. a provider switch), or to RE_EXTRACT when it was edited
+/// since, so its AI edges are rediscovered too.
 pub async fn reembed_mashes(
     conn: &Arc<Mutex<Connection>>,
     config: &EmbeddingConfig,
     progress: &Arc<Mutex<Option<PipelineProgress>>>,
 ) -> Result<u32, String> {
-    let mashes: Vec<(String, String, String, String)> = {
+    let mashes: Vec<ReembedRow> = {
         let conn = conn.lock().map_err(|e| e.to_string())?;
         let mut stmt = conn
-            .prepare("SELECT id, summary, context, memo FROM mashes WHERE status = 'RE_EMBED'")
+            .prepare(
+                "SELECT id, summary, context, memo, content_hash, requeued_from
+                 FROM mashes WHERE status = 'RE_EMBED'",
+            )
             .map_err(|e| e.to_string())?;
         let result = stmt
-            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?))
+            })
             .map_err(|e| e.to_string())?
             .collect::<Result<Vec<_>, _>>()
             .map_err(|e| e.to_string());
@@ -116,34 +166,38 @@ pub async fn reembed_mashes(
 
     let texts: Vec<String> = mashes
         .iter()
-        .map(|(_, summary, context, memo)| {
-            let mut parts = vec![summary.as_str()];
-            if !context.is_empty() {
-                parts.push(context.as_str());
-            }
-            if !memo.is_empty() {
-                parts.push(memo.as_str());
-            }
-            parts.join("\n")
-        })
+        .map(|(_, summary, context, memo, _, _)| embedding_text(summary, context, memo))
         .collect();
-    let embeddings = generate_embeddings(config, texts, EmbeddingTaskType::Document).await?;
+    let hashes: Vec<String> = texts.iter().map(|t| hash_text(t)).collect();
+    let embeddings = embed_documents(conn, config, texts, &hashes).await?;
 
     update_progress(progress, "re_embed", "saving", 0, total);
 
     let now = now_ms();
     let mut count = 0u32;
 
-    for (i, (id, _, _, _)) in mashes.iter().enumerate() {
+    for (i, (id, summary, context, memo, previous_hash, requeued_from)) in mashes.iter().enumerate() {
         if let Some(Some(ref embedding)) = embeddings.get(i) {
             let blob = serialize_embedding(embedding);
-            {
+            let hash = &hashes[i];
+            let status = match previous_hash {
+                Some(previous) if previous != hash => "RE_EXTRACT",
+                _ => requeued_from.as_deref().unwrap_or("JARRED"),
+            };
+            // A mash edited while the API call ran stays RE_EMBED for the next run
+            let updated = {
                 let conn = conn.lock().map_err(|e| e.to_string())?;
                 conn.execute(
-                    "UPDATE mashes SET status = 'JARRED', embedding = ?1, updated_at = ?2 WHERE id = ?3",
-                    params![blob, now, id],
+                    "UPDATE mashes SET status = ?1, embedding = ?2, content_hash = ?3, updated_at = ?4,
+                                       requeued_from = NULL
+                     WHERE id = ?5 AND status = 'RE_EMBED'
+                       AND summary = ?6 AND context = ?7 AND memo = ?8",
+                    params![status, blob, hash, now, id, summary, context, memo],
                 )
-                .map_err(|e| e.to_string())?;
+                .map_err(|e| e.to_string())?
+            };
+            if updated == 0 {
+                continue;
             }
             count += 1;
             update_progress(progress, "re_embed", "saving", count, total);
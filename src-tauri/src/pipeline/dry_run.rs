@@ -0,0 +1,207 @@
+use std::collections::{HashMap, HashSet};
+
+use rusqlite::Connection;
+
+use crate::ai::embedding::{embedding_batch_size, EmbeddingConfig};
+use crate::ai::relationship::{estimate_batch_usage, BATCH_SIZE};
+use crate::ai::usage::Usage;
+use crate::db::embedding_cache::cached_hashes;
+use crate::db::usage::{cost_of, get_price_table};
+use crate::models::{DryRunReport, EdgeReviewItem, ModelPrice};
+use crate::pipeline::jar::plan_reextract;
+use crate::similarity::{embedding_text, hash_text};
+
+/// Edges listed in a report; `edges_to_delete_count` still covers all of them.
+const EDGE_PREVIEW_LIMIT: usize = 100;
+
+/// Dry run of `reembed_all`: every DISTILLED, JARRED and RE_EMBED mash is
+/// embedded again (cache misses only), and mashes edited since their last
+/// embedding go on to re-extraction. Pairs are estimated from the stored vectors.
+pub fn preview_reembed(conn: &Connection, config: &EmbeddingConfig) -> Result<DryRunReport, String> {
+    let mashes: Vec<(String, String, Option<String>)> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, summary, context, memo, content_hash FROM mashes
+                 WHERE status IN ('DISTILLED', 'JARRED', 'RE_EMBED')",
+            )
+            .map_err(|e| e.to_string())?;
+        let result = stmt
+            .query_map([], |row| {
+                let summary: String = row.get(1)?;
+                let context: String = row.get(2)?;
+                let memo: String = row.get(3)?;
+                Ok((row.get(0)?, embedding_text(&summary, &context, &memo), row.get(4)?))
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string());
+        result?
+    };
+
+    let hashes: Vec<String> = mashes.iter().map(|(_, text, _)| hash_text(text)).collect();
+    let cached = cached_hashes(conn, config, &hashes)?;
+
+    let mut embedding_usage = Usage::default();
+    let mut misses = 0usize;
+    let mut reextract_ids = HashSet::new();
+    for ((id, text, previous_hash), hash) in mashes.iter().zip(&hashes) {
+        if !cached.contains(hash) {
+            misses += 1;
+            embedding_usage.add(&Usage::estimate(text.chars().count(), 0));
+        }
+        if previous_hash.as_ref().is_some_and(|p| p != hash) {
+            reextract_ids.insert(id.clone());
+        }
+    }
+
+    let mut report = preview_extraction(conn, "reembed_all", &reextract_ids)?;
+    report.mash_count = mashes.len() as u32;
+    report.embedding_texts = mashes.len() as u32;
+    report.cached_embeddings = (mashes.len() - misses) as u32;
+    report.embedding_calls = misses.div_ceil(embedding_batch_size(&config.provider)) as u32;
+    report.embedding_tokens = embedding_usage.prompt_tokens as i64;
+    report.estimated_cost = estimate_cost(conn, config, &report)?;
+    Ok(report)
+}
+
+/// Dry run of `reextract_relationships`: every JARRED mash (and any already
+/// waiting in RE_EXTRACT) loses its pending AI edges and is paired again.
+pub fn preview_reextract(conn: &Connection, config: &EmbeddingConfig) -> Result<DryRunReport, String> {
+    let ids: HashSet<String> = {
+        let mut stmt = conn
+            .prepare("SELECT id FROM mashes WHERE status IN ('JARRED', 'RE_EXTRACT')")
+            .map_err(|e| e.to_string())?;
+        let result = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<HashSet<_>, _>>()
+            .map_err(|e| e.to_string());
+        result?
+    };
+
+    let mut report = preview_extraction(conn, "reextract_relationships", &ids)?;
+    report.mash_count = ids.len() as u32;
+    report.estimated_cost = estimate_cost(conn, config, &report)?;
+    Ok(report)
+}
+
+/// Pairs, chat batches, chat tokens and deleted edges for re-extracting `ids`.
+fn preview_extraction(
+    conn: &Connection,
+    operation: &str,
+    ids: &HashSet<String>,
+) -> Result<DryRunReport, String> {
+    let (similar_pairs, candidates, relation_types) = if ids.is_empty() {
+        (0, vec![], vec![])
+    } else {
+        plan_reextract(conn, ids)?
+    };
+
+    let mut chat_usage = Usage::default();
+    let mut chat_batches = 0u32;
+    for batch in candidates.chunks(BATCH_SIZE) {
+        chat_usage.add(&estimate_batch_usage(&relation_types, batch)?);
+        chat_batches += 1;
+    }
+
+    let edges = edges_to_delete(conn, ids)?;
+    log::info!(
+        "Dry run {}: {} pairs, {} chat batches, {} edges to delete",
+        operation,
+        similar_pairs,
+        chat_batches,
+        edges.len()
+    );
+
+    Ok(DryRunReport {
+        operation: operation.to_string(),
+        mash_count: 0,
+        embedding_texts: 0,
+        cached_embeddings: 0,
+        embedding_calls: 0,
+        reextract_count: ids.len() as u32,
+        similar_pairs: similar_pairs as u32,
+        chat_batches,
+        embedding_tokens: 0,
+        chat_prompt_tokens: chat_usage.prompt_tokens as i64,
+        chat_completion_tokens: chat_usage.completion_tokens as i64,
+        estimated_cost: None,
+        edges_to_delete_count: edges.len() as u32,
+        edges_to_delete: edges.into_iter().take(EDGE_PREVIEW_LIMIT).collect(),
+    })
+}
+
+/// Pending AI edges touching `ids`, which re-extraction deletes; accepted
+/// edges survive it.
+fn edges_to_delete(conn: &Connection, ids: &HashSet<String>) -> Result<Vec<EdgeReviewItem>, String> {
+    if ids.is_empty() {
+        return Ok(vec![]);
+    }
+    let mut stmt = conn
+        .prepare(
+            "SELECT e.id, e.source_id, s.summary, e.target_id, t.summary, e.relation_type,
+                    e.confidence, e.rationale, e.model, e.extracted_at, e.evidence
+             FROM edges e
+             JOIN mashes s ON s.id = e.source_id
+             JOIN mashes t ON t.id = e.target_id
+             WHERE e.source = 'ai' AND e.review_state = 'pending'
+             ORDER BY e.confidence DESC, e.id ASC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(EdgeReviewItem {
+                id: row.get(0)?,
+                source_id: row.get(1)?,
+                source_summary: row.get(2)?,
+                target_id: row.get(3)?,
+                target_summary: row.get(4)?,
+                relation_type: row.get(5)?,
+                confidence: row.get(6)?,
+                rationale: row.get(7)?,
+                model: row.get(8)?,
+                extracted_at: row.get(9)?,
+                evidence: row.get(10)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut edges = Vec::new();
+    for row in rows {
+        let edge = row.map_err(|e| e.to_string())?;
+        if ids.contains(&edge.source_id) || ids.contains(&edge.target_id) {
+            edges.push(edge);
+        }
+    }
+    Ok(edges)
+}
+
+/// Cost of the report's tokens at the configured prices; None when a model
+/// that would be called has no price.
+fn estimate_cost(
+    conn: &Connection,
+    config: &EmbeddingConfig,
+    report: &DryRunReport,
+) -> Result<Option<f64>, String> {
+    let prices = get_price_table(conn)?;
+    let embedding = priced(&prices, &config.embedding_model, report.embedding_tokens, 0);
+    let chat = priced(
+        &prices,
+        &config.chat_model,
+        report.chat_prompt_tokens,
+        report.chat_completion_tokens,
+    );
+    Ok(embedding.zip(chat).map(|(e, c)| e + c))
+}
+
+fn priced(
+    prices: &HashMap<String, ModelPrice>,
+    model: &str,
+    prompt_tokens: i64,
+    completion_tokens: i64,
+) -> Option<f64> {
+    if prompt_tokens == 0 && completion_tokens == 0 {
+        return Some(0.0);
+    }
+    cost_of(prices, model, prompt_tokens, completion_tokens)
+}
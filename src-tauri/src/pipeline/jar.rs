@@ -1,12 +1,15 @@
 use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 
 use crate::ai::embedding::EmbeddingConfig;
-use crate::ai::relationship::{extract_batch, extract_relations, RelationCandidate};
+use crate::ai::relationship::{estimate_batch_usage, extract_batch, RelationCandidate, BATCH_SIZE};
+use crate::db::edge_review::{get_rejected_pairs, is_pair_rejected};
+use crate::db::edges::upsert_ai_edge;
 use crate::db::now_ms;
-use crate::models::{PipelineProgress, SimilarPair};
+use crate::models::{PipelineProgress, PipelineRunResult, RelationTypeDef, SimilarPair};
+use crate::pipeline::budget::pause_if_over_budget;
 use crate::similarity::{deserialize_embedding, find_similar_in_batch, find_similar_mashes};
 
 fn update_progress(
@@ -26,15 +29,30 @@ fn update_progress(
     }
 }
 
+/// Similarity threshold, per-mash pair limit and relation vocabulary for extraction.
+fn extraction_settings(conn: &Connection) -> Result<(f32, usize, Vec<RelationTypeDef>), String> {
+    let threshold = crate::db::settings::get_setting(conn, "pipeline_threshold")?
+        .and_then(|v| v.parse::<f32>().ok())
+        .unwrap_or(0.3);
+    let top_k = crate::db::settings::get_setting(conn, "pipeline_top_k")?
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(5);
+    let relation_types = crate::db::relation_types::get_relation_types(conn)?;
+    Ok((threshold, top_k, relation_types))
+}
+
 struct DistilledMash {
     id: String,
     summary: String,
     embedding: Option<Vec<f32>>,
 }
 
+/// DISTILLED → JARRED. Extraction stops between batches once a spending cap
+/// would be exceeded; mashes with pairs left unsent stay DISTILLED for the next run.
 pub async fn jar_mashes(
     conn: &Arc<Mutex<Connection>>,
     config: &EmbeddingConfig,
+    result: &mut PipelineRunResult,
 ) -> Result<u32, String> {
     // Step 1: Read DISTILLED mashes (sync)
     let distilled: Vec<DistilledMash> = {
@@ -64,16 +82,10 @@ pub async fn jar_mashes(
 
     log::info!("Jarring {} mashes", distilled.len());
 
-    // Read similarity settings
-    let (threshold, pipeline_top_k) = {
+    // Read similarity settings and the relation vocabulary
+    let (threshold, pipeline_top_k, relation_types) = {
         let conn = conn.lock().map_err(|e| e.to_string())?;
-        let threshold = crate::db::settings::get_setting(&conn, "pipeline_threshold")?
-            .and_then(|v| v.parse::<f32>().ok())
-            .unwrap_or(0.3);
-        let top_k = crate::db::settings::get_setting(&conn, "pipeline_top_k")?
-            .and_then(|v| v.parse::<usize>().ok())
-            .unwrap_or(5);
-        (threshold, top_k)
+        extraction_settings(&conn)?
     };
 
     // Step 2: Find similar pairs (sync - all in-memory)
@@ -90,32 +102,45 @@ pub async fn jar_mashes(
     };
     log::info!("Built {} candidates for AI extraction", candidates.len());
 
-    // Step 4: AI relationship extraction (async)
-    let relations = extract_relations(config, candidates).await?;
-    log::info!("Extracted {} relations", relations.len());
-
-    // Step 5: Create edges and update status (sync)
-    let conn = conn.lock().map_err(|e| e.to_string())?;
-    let now = now_ms();
+    // Step 4: AI relationship extraction per batch (async), then create edges (sync)
     let mut edges_created = 0u32;
-    for rel in &relations {
-        let result = conn.execute(
-            "INSERT INTO edges (source_id, target_id, relation_type, source, confidence, created_at, updated_at)
-             VALUES (?1, ?2, ?3, 'ai', ?4, ?5, ?6)
-             ON CONFLICT(source_id, target_id) DO UPDATE SET
-                relation_type = excluded.relation_type,
-                confidence = excluded.confidence,
-                updated_at = excluded.updated_at",
-            params![rel.source_id, rel.target_id, rel.relation, rel.confidence, now, now],
-        );
-        match result {
-            Ok(_) => edges_created += 1,
-            Err(e) => log::warn!("Failed to create edge: {}", e),
+    let mut unsent = HashSet::new();
+    let batches: Vec<&[RelationCandidate]> = candidates.chunks(BATCH_SIZE).collect();
+    for (batch_idx, batch) in batches.iter().enumerate() {
+        let estimate = estimate_batch_usage(&relation_types, batch)?;
+        if pause_if_over_budget(conn, "jar", Some((&config.chat_model, &estimate)), result) {
+            unsent.extend(
+                batches[batch_idx..]
+                    .iter()
+                    .flat_map(|b| b.iter())
+                    .flat_map(|c| [c.source_id.clone(), c.target_id.clone()]),
+            );
+            break;
+        }
+
+        let relations = match extract_batch(config, &relation_types, batch).await {
+            Ok(r) => r,
+            Err(e) => {
+                log::warn!("Relationship extraction batch failed, skipping: {}", e);
+                continue;
+            }
+        };
+
+        let conn = conn.lock().map_err(|e| e.to_string())?;
+        for rel in &relations {
+            match upsert_ai_edge(&conn, &relation_types, rel, &config.chat_model) {
+                Ok(true) => edges_created += 1,
+                Ok(false) => {}
+                Err(e) => log::warn!("Failed to create edge: {}", e),
+            }
         }
     }
 
+    // Step 5: Update status (sync)
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    let now = now_ms();
     let mut jarred = 0u32;
-    for mash in &distilled {
+    for mash in distilled.iter().filter(|m| !unsent.contains(&m.id)) {
         conn.execute(
             "UPDATE mashes SET status = 'JARRED', updated_at = ?1 WHERE id = ?2",
             params![now, mash.id],
@@ -173,10 +198,13 @@ fn find_all_similar_pairs(
 }
 
 /// RE_EXTRACT: re-extract relationships only (embeddings already exist), then go to JARRED.
+/// Stops between batches once a spending cap would be exceeded; mashes with
+/// pairs left unsent stay RE_EXTRACT for the next run.
 pub async fn reextract_mashes(
     conn: &Arc<Mutex<Connection>>,
     config: &EmbeddingConfig,
     progress: &Arc<Mutex<Option<PipelineProgress>>>,
+    result: &mut PipelineRunResult,
 ) -> Result<u32, String> {
     let targets: Vec<DistilledMash> = {
         let conn = conn.lock().map_err(|e| e.to_string())?;
@@ -205,12 +233,13 @@ pub async fn reextract_mashes(
     let total = targets.len() as u32;
     log::info!("Re-extracting relationships for {} mashes", total);
 
-    // Delete AI edges only for RE_EXTRACT target mashes (not all edges globally)
+    // Delete unreviewed AI edges only for RE_EXTRACT target mashes (not all edges
+    // globally); accepted edges survive re-extraction
     {
         let conn = conn.lock().map_err(|e| e.to_string())?;
         let deleted = conn
             .execute(
-                "DELETE FROM edges WHERE source = 'ai'
+                "DELETE FROM edges WHERE source = 'ai' AND review_state = 'pending'
                  AND (source_id IN (SELECT id FROM mashes WHERE status = 'RE_EXTRACT')
                    OR target_id IN (SELECT id FROM mashes WHERE status = 'RE_EXTRACT'))",
                 [],
@@ -221,15 +250,9 @@ pub async fn reextract_mashes(
 
     update_progress(progress, "re_extract", "similarity", 0, total);
 
-    let (threshold, pipeline_top_k) = {
+    let (threshold, pipeline_top_k, relation_types) = {
         let conn = conn.lock().map_err(|e| e.to_string())?;
-        let threshold = crate::db::settings::get_setting(&conn, "pipeline_threshold")?
-            .and_then(|v| v.parse::<f32>().ok())
-            .unwrap_or(0.3);
-        let top_k = crate::db::settings::get_setting(&conn, "pipeline_top_k")?
-            .and_then(|v| v.parse::<usize>().ok())
-            .unwrap_or(5);
-        (threshold, top_k)
+        extraction_settings(&conn)?
     };
 
     let similar_pairs = {
@@ -244,17 +267,38 @@ pub async fn reextract_mashes(
     };
     log::info!("Built {} candidates for re-extraction", candidates.len());
 
-    // Process candidates in batches: API call → save edges → update mash status per batch
+    // Process candidates in batches: API call → save edges → update mash status
+    // once the last batch involving the mash is through
     let mut edges_created = 0u32;
-    let mut processed_mash_ids = std::collections::HashSet::new();
+    let mut processed_mash_ids = HashSet::new();
+    let mut unsent = HashSet::new();
     let mut count = 0u32;
-    let batches: Vec<&[RelationCandidate]> = candidates.chunks(5).collect();
+    let batches: Vec<&[RelationCandidate]> = candidates.chunks(BATCH_SIZE).collect();
     let total_batches = batches.len();
+    let last_batch: HashMap<&str, usize> = batches
+        .iter()
+        .enumerate()
+        .flat_map(|(i, b)| {
+            b.iter()
+                .flat_map(move |c| [(c.source_id.as_str(), i), (c.target_id.as_str(), i)])
+        })
+        .collect();
 
-    for (batch_idx, batch) in batches.into_iter().enumerate() {
+    for (batch_idx, batch) in batches.iter().enumerate() {
         update_progress(progress, "re_extract", "api", batch_idx as u32, total_batches as u32);
 
-        let relations = match extract_batch(config, batch).await {
+        let estimate = estimate_batch_usage(&relation_types, batch)?;
+        if pause_if_over_budget(conn, "re_extract", Some((&config.chat_model, &estimate)), result) {
+            unsent.extend(
+                batches[batch_idx..]
+                    .iter()
+                    .flat_map(|b| b.iter())
+                    .flat_map(|c| [c.source_id.as_str(), c.target_id.as_str()]),
+            );
+            break;
+        }
+
+        let relations = match extract_batch(config, &relation_types, batch).await {
             Ok(r) => r,
             Err(e) => {
                 log::warn!("Relationship extraction batch failed, skipping: {}", e);
@@ -267,45 +311,39 @@ pub async fn reextract_mashes(
 
         // Save edges
         for rel in &relations {
-            let result = conn.execute(
-                "INSERT INTO edges (source_id, target_id, relation_type, source, confidence, created_at, updated_at)
-                 VALUES (?1, ?2, ?3, 'ai', ?4, ?5, ?6)
-                 ON CONFLICT(source_id, target_id) DO UPDATE SET
-                    relation_type = excluded.relation_type,
-                    confidence = excluded.confidence,
-                    updated_at = excluded.updated_at
-                 WHERE edges.source = 'ai'",
-                params![rel.source_id, rel.target_id, rel.relation, rel.confidence, now, now],
-            );
-            match result {
-                Ok(_) => edges_created += 1,
+            match upsert_ai_edge(&conn, &relation_types, rel, &config.chat_model) {
+                Ok(true) => edges_created += 1,
+                Ok(false) => {}
                 Err(e) => log::warn!("Failed to create edge: {}", e),
             }
         }
 
-        // Update mash status for mashes involved in this batch
-        for candidate in batch {
-            for id in [&candidate.source_id, &candidate.target_id] {
-                if processed_mash_ids.insert(id.clone()) {
-                    if targets.iter().any(|t| t.id == *id) {
-                        conn.execute(
-                            "UPDATE mashes SET status = 'JARRED', updated_at = ?1 WHERE id = ?2 AND status = 'RE_EXTRACT'",
-                            params![now, id],
-                        )
-                        .map_err(|e| e.to_string())?;
-                        count += 1;
-                    }
+        // Update mash status for target mashes whose last batch this was
+        for candidate in batch.iter() {
+            for id in [candidate.source_id.as_str(), candidate.target_id.as_str()] {
+                if last_batch[id] == batch_idx
+                    && targets.iter().any(|t| t.id == id)
+                    && processed_mash_ids.insert(id)
+                {
+                    conn.execute(
+                        "UPDATE mashes SET status = 'JARRED', updated_at = ?1 WHERE id = ?2 AND status = 'RE_EXTRACT'",
+                        params![now, id],
+                    )
+                    .map_err(|e| e.to_string())?;
+                    count += 1;
                 }
             }
         }
     }
 
-    // Update remaining mashes that had no candidates (no similar pairs found)
+    // Update remaining mashes: those with no candidates (no similar pairs found)
+    // or whose last batch failed. Mashes with unsent pairs stay queued.
     {
         let now = now_ms();
         let conn = conn.lock().map_err(|e| e.to_string())?;
         for mash in &targets {
-            if !processed_mash_ids.contains(&mash.id) {
+            let id = mash.id.as_str();
+            if !processed_mash_ids.contains(id) && !unsent.contains(id) {
                 conn.execute(
                     "UPDATE mashes SET status = 'JARRED', updated_at = ?1 WHERE id = ?2 AND status = 'RE_EXTRACT'",
                     params![now, mash.id],
@@ -322,6 +360,40 @@ pub async fn reextract_mashes(
     Ok(count)
 }
 
+/// Similar pairs and relation candidates re-extraction would build for the
+/// mashes in `ids`, using their stored embeddings. Read-only, for dry runs.
+pub fn plan_reextract(
+    conn: &Connection,
+    ids: &HashSet<String>,
+) -> Result<(usize, Vec<RelationCandidate>, Vec<RelationTypeDef>), String> {
+    let mut ids: Vec<&String> = ids.iter().collect();
+    ids.sort();
+
+    let mut stmt = conn
+        .prepare("SELECT id, summary, embedding FROM mashes WHERE id = ?1")
+        .map_err(|e| e.to_string())?;
+    let mut targets = Vec::with_capacity(ids.len());
+    for id in ids {
+        let target = stmt
+            .query_row(params![id], |row| {
+                let embedding_blob: Option<Vec<u8>> = row.get(2)?;
+                Ok(DistilledMash {
+                    id: row.get(0)?,
+                    summary: row.get(1)?,
+                    embedding: embedding_blob.map(|b| deserialize_embedding(&b)),
+                })
+            })
+            .optional()
+            .map_err(|e| e.to_string())?;
+        targets.extend(target);
+    }
+
+    let (threshold, pipeline_top_k, relation_types) = extraction_settings(conn)?;
+    let pairs = find_all_similar_pairs_reextract(conn, &targets, threshold, pipeline_top_k)?;
+    let candidates = build_candidates(conn, &pairs, &targets)?;
+    Ok((pairs.len(), candidates, relation_types))
+}
+
 /// For RE_EXTRACT: find similar pairs among the targets and existing JARRED mashes.
 fn find_all_similar_pairs_reextract(
     conn: &Connection,
@@ -376,6 +448,12 @@ fn build_candidates(
         return Ok(vec![]);
     }
 
+    let rejected = get_rejected_pairs(conn)?;
+    let pairs: Vec<&SimilarPair> = pairs
+        .iter()
+        .filter(|p| !is_pair_rejected(&rejected, &p.source_id, &p.target_id))
+        .collect();
+
     let mut summary_map: HashMap<String, String> =
         distilled.iter().map(|m| (m.id.clone(), m.summary.clone())).collect();
 
@@ -404,6 +482,7 @@ fn build_candidates(
             source_summary: summary_map[&p.source_id].clone(),
             target_id: p.target_id.clone(),
             target_summary: summary_map[&p.target_id].clone(),
+            evidence: None,
         })
         .collect())
 }
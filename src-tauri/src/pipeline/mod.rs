@@ -1,4 +1,9 @@
 pub mod backfill;
+pub mod budget;
+pub mod cluster;
+pub mod digest;
 pub mod distill;
+pub mod dry_run;
 pub mod jar;
+pub mod questions;
 pub mod scheduler;
@@ -0,0 +1,115 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use rusqlite::Connection;
+
+use crate::ai::embedding::EmbeddingConfig;
+use crate::ai::relationship::{estimate_batch_usage, extract_batch, BATCH_SIZE};
+use crate::db;
+use crate::db::edges::upsert_ai_edge;
+use crate::models::{PipelineProgress, PipelineRunResult};
+use crate::pipeline::budget::pause_if_over_budget;
+
+/// Question/answer pairs sent to the model per run; the rest wait for later runs.
+const RESOLUTION_CANDIDATES_PER_RUN: usize = 30;
+
+/// Similar decisions and insights considered per open question.
+const ANSWERS_PER_QUESTION: usize = 3;
+
+fn update_progress(
+    progress: &Arc<Mutex<Option<PipelineProgress>>>,
+    phase: &str,
+    step: &str,
+    current: u32,
+    total: u32,
+) {
+    if let Ok(mut p) = progress.lock() {
+        *p = Some(PipelineProgress {
+            phase: phase.to_string(),
+            step: step.to_string(),
+            current,
+            total,
+        });
+    }
+}
+
+/// Propose RESOLVED_BY edges from open questions and problems to similar
+/// decisions and insights. Proposals land as pending edges; accepting one
+/// closes the question. Stops between batches once a spending cap would be
+/// exceeded; untried pairs come up again next run.
+pub async fn propose_resolutions(
+    conn: &Arc<Mutex<Connection>>,
+    config: &EmbeddingConfig,
+    progress: &Arc<Mutex<Option<PipelineProgress>>>,
+    result: &mut PipelineRunResult,
+) -> Result<u32, String> {
+    let (candidates, relation_types) = {
+        let conn = conn.lock().map_err(|e| e.to_string())?;
+        let threshold = db::settings::get_setting(&conn, "pipeline_threshold")?
+            .and_then(|v| v.parse::<f32>().ok())
+            .unwrap_or(0.3);
+        let candidates = db::questions::resolution_candidates(
+            &conn,
+            ANSWERS_PER_QUESTION,
+            threshold,
+            RESOLUTION_CANDIDATES_PER_RUN,
+        )?;
+        (candidates, db::relation_types::get_relation_types(&conn)?)
+    };
+    if candidates.is_empty() {
+        return Ok(0);
+    }
+    let resolved_by: Vec<_> = relation_types
+        .iter()
+        .filter(|t| t.code == "RESOLVED_BY")
+        .cloned()
+        .collect();
+    if resolved_by.is_empty() {
+        log::warn!("Resolutions: RESOLVED_BY is not in the relation registry, skipping");
+        return Ok(0);
+    }
+
+    let total = candidates.len() as u32;
+    log::info!("Resolutions: checking {} question/answer pairs", total);
+
+    // Batch by batch, so pairs in a failed batch are not marked as tried
+    let mut proposed = 0u32;
+    let mut checked = 0u32;
+    for batch in candidates.chunks(BATCH_SIZE) {
+        update_progress(progress, "resolutions", "api", checked, total);
+        checked += batch.len() as u32;
+        let estimate = estimate_batch_usage(&resolved_by, batch)?;
+        if pause_if_over_budget(conn, "resolutions", Some((&config.chat_model, &estimate)), result) {
+            break;
+        }
+        let relations = match extract_batch(config, &resolved_by, batch).await {
+            Ok(r) => r,
+            Err(e) => {
+                log::warn!("Resolutions: extraction batch failed, skipping: {}", e);
+                continue;
+            }
+        };
+
+        // Only keep edges that run from the question to its proposed answer.
+        let pairs: HashSet<(&str, &str)> = batch
+            .iter()
+            .map(|c| (c.source_id.as_str(), c.target_id.as_str()))
+            .collect();
+
+        let conn = conn.lock().map_err(|e| e.to_string())?;
+        for rel in relations
+            .iter()
+            .filter(|r| pairs.contains(&(r.source_id.as_str(), r.target_id.as_str())))
+        {
+            match upsert_ai_edge(&conn, &relation_types, rel, &config.chat_model) {
+                Ok(true) => proposed += 1,
+                Ok(false) => {}
+                Err(e) => log::warn!("Resolutions: failed to create edge: {}", e),
+            }
+        }
+        db::questions::record_resolution_attempts(&conn, batch)?;
+    }
+    update_progress(progress, "resolutions", "api", total, total);
+
+    Ok(proposed)
+}
@@ -6,7 +6,8 @@ use tokio::sync::Notify;
 
 use crate::ai::embedding::{resolve_embedding_config, EmbeddingConfig};
 use crate::db;
-use crate::models::PipelineProgress;
+use crate::pipeline::budget::pause_if_over_budget;
+use crate::models::{PipelineProgress, PipelineRunResult};
 
 pub struct PipelineSchedulerState {
     interval_min: Arc<AtomicU64>,
@@ -14,6 +15,7 @@ pub struct PipelineSchedulerState {
     next_run_ms: Arc<AtomicU64>,
     running: Arc<AtomicBool>,
     progress: Arc<Mutex<Option<PipelineProgress>>>,
+    last_result: Arc<Mutex<PipelineRunResult>>,
 }
 
 impl PipelineSchedulerState {
@@ -24,6 +26,7 @@ impl PipelineSchedulerState {
             next_run_ms: Arc::new(AtomicU64::new(0)),
             running: Arc::new(AtomicBool::new(false)),
             progress: Arc::new(Mutex::new(None)),
+            last_result: Arc::new(Mutex::new(PipelineRunResult::default())),
         }
     }
 
@@ -37,6 +40,11 @@ impl PipelineSchedulerState {
         self.progress.lock().ok().and_then(|p| p.clone())
     }
 
+    /// What the most recent run skipped for budget reasons.
+    pub fn last_result(&self) -> PipelineRunResult {
+        self.last_result.lock().map(|r| r.clone()).unwrap_or_default()
+    }
+
     pub fn update_interval(&self, minutes: u64) {
         self.interval_min.store(minutes, Ordering::SeqCst);
         self.notify.notify_one();
@@ -46,11 +54,14 @@ impl PipelineSchedulerState {
         &self,
         conn: &Arc<Mutex<Connection>>,
         config: &EmbeddingConfig,
-    ) -> Result<(), String> {
+    ) -> Result<PipelineRunResult, String> {
         if self.running.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err() {
             return Err("Pipeline is already running".to_string());
         }
         let result = run_pipeline(conn, config, &self.progress).await;
+        if let (Ok(run), Ok(mut last)) = (&result, self.last_result.lock()) {
+            *last = run.clone();
+        }
         self.clear_progress();
         self.running.store(false, Ordering::SeqCst);
         result
@@ -75,6 +86,7 @@ impl PipelineSchedulerState {
         let next_run_ms = self.next_run_ms.clone();
         let running = self.running.clone();
         let progress = self.progress.clone();
+        let last_result = self.last_result.clone();
 
         tauri::async_runtime::spawn(async move {
             let mut run_count = 0u64;
@@ -118,8 +130,13 @@ impl PipelineSchedulerState {
                     log::info!("Pipeline: skipping scheduled run (already running)");
                     continue;
                 }
-                if let Err(e) = run_pipeline(&conn, &config, &progress).await {
-                    log::error!("Pipeline run failed: {}", e);
+                match run_pipeline(&conn, &config, &progress).await {
+                    Ok(run) => {
+                        if let Ok(mut last) = last_result.lock() {
+                            *last = run;
+                        }
+                    }
+                    Err(e) => log::error!("Pipeline run failed: {}", e),
                 }
                 if let Ok(mut p) = progress.lock() { *p = None; }
                 running.store(false, Ordering::SeqCst);
@@ -134,44 +151,113 @@ impl PipelineSchedulerState {
 
                 run_count += 1;
 
-                // Backfill every 5 runs
-                if run_count % 5 == 0 {
-                    if let Err(e) =
-                        crate::pipeline::backfill::backfill_isolated_nodes(&conn, &config).await
+                // Backfill every 5 runs and the digest once its window has
+                // passed, budget permitting
+                let mut paused = PipelineRunResult::default();
+                if run_count % 5 == 0 && !pause_if_over_budget(&conn, "backfill", None, &mut paused) {
+                    let config = config.tracked(&conn, "backfill", None);
+                    if let Err(e) = crate::pipeline::backfill::backfill_isolated_nodes(
+                        &conn,
+                        &config,
+                        &mut paused,
+                    )
+                    .await
                     {
                         log::error!("Backfill failed: {}", e);
                     }
                 }
+
+                if !pause_if_over_budget(&conn, "digest", None, &mut paused) {
+                    let digest_config = config.tracked(&conn, "digest", None);
+                    match crate::pipeline::digest::generate_due_digest(&conn, &digest_config).await {
+                        Ok(Some(digest)) => log::info!("Pipeline: wrote digest {}", digest.id),
+                        Ok(None) => {}
+                        Err(e) => log::error!("Digest failed: {}", e),
+                    }
+                }
+
+                if let (Some(reason), Ok(mut last)) = (paused.paused_reason, last_result.lock()) {
+                    last.paused_phases.extend(paused.paused_phases);
+                    last.paused_reason.get_or_insert(reason);
+                }
             }
         });
     }
 }
 
+/// Run every phase once. The chat phases (jar, re-extract, cluster summaries,
+/// resolutions) are skipped while a spending cap is reached, and stop between
+/// calls once the next one would exceed a cap, leaving the rest queued.
 async fn run_pipeline(
     conn: &Arc<Mutex<Connection>>,
     config: &EmbeddingConfig,
     progress: &Arc<Mutex<Option<PipelineProgress>>>,
-) -> Result<(), String> {
-    log::info!("Pipeline: starting run");
+) -> Result<PipelineRunResult, String> {
+    let run_id = uuid::Uuid::new_v4().to_string();
+    log::info!("Pipeline: starting run {}", run_id);
+    let phase = |name: &str| config.tracked(conn, name, Some(&run_id));
+    let mut result = PipelineRunResult::default();
 
     // Normal flow: ON_STILL → DISTILLED → JARRED
-    let distilled = crate::pipeline::distill::distill_mashes(conn, config).await?;
+    let distilled = crate::pipeline::distill::distill_mashes(conn, &phase("distill")).await?;
     log::info!("Pipeline: distilled {} mashes", distilled);
 
-    let jarred = crate::pipeline::jar::jar_mashes(conn, config).await?;
-    log::info!("Pipeline: jarred {} mashes", jarred);
+    if !pause_if_over_budget(conn, "jar", None, &mut result) {
+        let jarred = crate::pipeline::jar::jar_mashes(conn, &phase("jar"), &mut result).await?;
+        log::info!("Pipeline: jarred {} mashes", jarred);
+    }
 
-    // RE_EMBED → embed only → JARRED
-    let reembedded = crate::pipeline::distill::reembed_mashes(conn, config, progress).await?;
+    // RE_EMBED → embed → JARRED, or RE_EXTRACT when the text was edited
+    let reembedded =
+        crate::pipeline::distill::reembed_mashes(conn, &phase("re_embed"), progress).await?;
     if reembedded > 0 {
         log::info!("Pipeline: re-embedded {} mashes", reembedded);
     }
 
     // RE_EXTRACT → extract only → JARRED
-    let reextracted = crate::pipeline::jar::reextract_mashes(conn, config, progress).await?;
-    if reextracted > 0 {
-        log::info!("Pipeline: re-extracted {} mashes", reextracted);
+    if !pause_if_over_budget(conn, "re_extract", None, &mut result) {
+        let reextracted = crate::pipeline::jar::reextract_mashes(
+            conn,
+            &phase("re_extract"),
+            progress,
+            &mut result,
+        )
+        .await?;
+        if reextracted > 0 {
+            log::info!("Pipeline: re-extracted {} mashes", reextracted);
+        }
+    }
+
+    // Regroup the graph into topic clusters
+    let clusters = crate::pipeline::cluster::refresh_clusters(conn)?;
+    log::info!("Pipeline: {} topic clusters", clusters.len());
+
+    if !pause_if_over_budget(conn, "cluster_summary", None, &mut result) {
+        let summarized = crate::pipeline::cluster::summarize_clusters(
+            conn,
+            &phase("cluster_summary"),
+            progress,
+            &mut result,
+        )
+        .await?;
+        if summarized > 0 {
+            log::info!("Pipeline: summarized {} clusters", summarized);
+        }
+    }
+
+    // Propose answers for open questions and problems
+    if !pause_if_over_budget(conn, "resolutions", None, &mut result) {
+        let resolutions = crate::pipeline::questions::propose_resolutions(
+            conn,
+            &phase("resolutions"),
+            progress,
+            &mut result,
+        )
+        .await?;
+        if resolutions > 0 {
+            log::info!("Pipeline: proposed {} resolutions", resolutions);
+        }
     }
 
-    Ok(())
+    Ok(result)
 }
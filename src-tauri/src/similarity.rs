@@ -16,6 +16,34 @@ pub fn deserialize_embedding(blob: &[u8]) -> Vec<f32> {
         .collect()
 }
 
+/// The text a mash is embedded from: summary, context and memo, skipping empty fields.
+pub fn embedding_text(summary: &str, context: &str, memo: &str) -> String {
+    let mut parts = vec![summary];
+    if !context.is_empty() {
+        parts.push(context);
+    }
+    if !memo.is_empty() {
+        parts.push(memo);
+    }
+    parts.join("\n")
+}
+
+/// Stable 64-bit FNV-1a hash of `text`, hex-encoded. Stored in the database,
+/// so it must not change between builds.
+pub fn hash_text(text: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in text.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Hash of the text a mash is embedded from; see `embedding_text`.
+pub fn content_hash(summary: &str, context: &str, memo: &str) -> String {
+    hash_text(&embedding_text(summary, context, memo))
+}
+
 pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
     let mut dot = 0.0f64;
     let mut norm_a = 0.0f64;
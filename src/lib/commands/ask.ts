@@ -0,0 +1,66 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type { GraphData } from './graph';
+
+export interface Conversation {
+	id: string;
+	title: string;
+	createdAt: number;
+	updatedAt: number;
+}
+
+export interface ConversationMessage {
+	id: number;
+	conversationId: string;
+	role: 'user' | 'assistant';
+	content: string;
+	/** Mash IDs the answer cites; empty for questions. */
+	citedIds: string[];
+	model: string | null;
+	createdAt: number;
+}
+
+export interface AskResponse {
+	conversationId: string;
+	message: ConversationMessage;
+	/** The cited mashes and the edges between them. */
+	graph: GraphData;
+}
+
+/** A streamed piece of an answer, delivered as the `chat-chunk` event. */
+export interface ChatChunk {
+	requestId: string;
+	delta: string;
+}
+
+/**
+ * Ask a question; pass `conversationId` to follow up on an earlier answer.
+ * The answer streams as `chat-chunk` events tagged with `requestId`.
+ */
+export function ask(question: string, conversationId?: string, requestId?: string): Promise<AskResponse> {
+	return invoke<AskResponse>('ask', { question, conversationId, requestId });
+}
+
+/** Stop a streaming answer; resolves to false when it had already finished. */
+export function cancelChat(requestId: string): Promise<boolean> {
+	return invoke<boolean>('cancel_chat', { requestId });
+}
+
+/** Receive streamed text for one request until the returned function is called. */
+export function onChatChunk(requestId: string, handler: (delta: string) => void): Promise<UnlistenFn> {
+	return listen<ChatChunk>('chat-chunk', (event) => {
+		if (event.payload.requestId === requestId) handler(event.payload.delta);
+	});
+}
+
+export function getConversations(): Promise<Conversation[]> {
+	return invoke<Conversation[]>('get_conversations');
+}
+
+export function getConversationMessages(id: string): Promise<ConversationMessage[]> {
+	return invoke<ConversationMessage[]>('get_conversation_messages', { id });
+}
+
+export function deleteConversation(id: string): Promise<void> {
+	return invoke('delete_conversation', { id });
+}
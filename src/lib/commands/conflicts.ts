@@ -0,0 +1,45 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export interface ConflictResolution {
+	supersedingId: string | null;
+	rationale: string;
+	model: string;
+	proposedAt: number;
+}
+
+export interface ConflictItem {
+	edgeId: number;
+	sourceId: string;
+	sourceSummary: string;
+	sourceType: string;
+	targetId: string;
+	targetSummary: string;
+	targetType: string;
+	confidence: number;
+	rationale: string | null;
+	edgeSource: string;
+	reviewState: 'pending' | 'accepted';
+	updatedAt: number;
+	decisionConflict: boolean;
+	resolution: ConflictResolution | null;
+}
+
+export interface ConflictGroup {
+	clusterId: number | null;
+	clusterTitle: string | null;
+	conflicts: ConflictItem[];
+}
+
+export interface ConflictsReport {
+	groups: ConflictGroup[];
+	total: number;
+	unresolvedDecisions: number;
+}
+
+export function getConflictsReport(): Promise<ConflictsReport> {
+	return invoke<ConflictsReport>('get_conflicts_report');
+}
+
+export function reviewConflicts(limit?: number): Promise<ConflictsReport> {
+	return invoke<ConflictsReport>('review_conflicts', { limit });
+}
@@ -0,0 +1,30 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export interface Digest {
+	id: number;
+	periodStart: number;
+	periodEnd: number;
+	markdown: string;
+	model: string | null;
+	createdAt: number;
+}
+
+export function getDigests(): Promise<Digest[]> {
+	return invoke<Digest[]>('get_digests');
+}
+
+/** Write a digest for the last `days` days now; defaults to the configured window. */
+export function generateDigestNow(days?: number): Promise<Digest> {
+	return invoke<Digest>('generate_digest_now', { days });
+}
+
+/** Download a digest as a Markdown file named `filename`. */
+export async function exportDigest(id: number, filename: string): Promise<void> {
+	const markdown = await invoke<string>('export_digest', { id });
+	const url = URL.createObjectURL(new Blob([markdown], { type: 'text/markdown' }));
+	const link = document.createElement('a');
+	link.href = url;
+	link.download = filename;
+	link.click();
+	URL.revokeObjectURL(url);
+}
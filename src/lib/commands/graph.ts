@@ -8,6 +8,7 @@ export interface GraphNode {
 	context: string;
 	memo: string;
 	status: string;
+	clusterId: number | null;
 	createdAt: number;
 	updatedAt: number;
 }
@@ -19,6 +20,11 @@ export interface GraphEdge {
 	relationType: string;
 	source: string;
 	confidence: number;
+	rationale: string | null;
+	model: string | null;
+	extractedAt: number | null;
+	reviewState: 'pending' | 'accepted';
+	evidence: string | null;
 	createdAt: number;
 	updatedAt: number;
 }
@@ -28,16 +34,94 @@ export interface GraphData {
 	edges: GraphEdge[];
 }
 
+export interface RetypedEdge {
+	edge: GraphEdge;
+	previousRelationType: string;
+}
+
+export interface GraphDiff {
+	addedNodes: GraphNode[];
+	removedNodes: GraphNode[];
+	addedEdges: GraphEdge[];
+	removedEdges: GraphEdge[];
+	retypedEdges: RetypedEdge[];
+}
+
+export interface GraphPath {
+	nodeIds: string[];
+	edgeIds: number[];
+	cost: number;
+}
+
+export interface PathResult {
+	graph: GraphData;
+	paths: GraphPath[];
+}
+
+export interface RelationTypeDef {
+	code: string;
+	description: string;
+	directed: boolean;
+	inverseLabel: string | null;
+	position: number;
+	createdAt: number;
+	updatedAt: number;
+}
+
+export interface EdgeReviewItem {
+	id: number;
+	sourceId: string;
+	sourceSummary: string;
+	targetId: string;
+	targetSummary: string;
+	relationType: string;
+	confidence: number;
+	rationale: string | null;
+	model: string | null;
+	extractedAt: number | null;
+	evidence: string | null;
+}
+
+export interface Cluster {
+	id: number;
+	size: number;
+	title: string | null;
+	summary: string | null;
+	summaryModel: string | null;
+	summarizedAt: number | null;
+	createdAt: number;
+	updatedAt: number;
+}
+
+export interface NodeMetrics {
+	id: string;
+	degree: number;
+	betweenness: number;
+	pagerank: number;
+	clustering: number;
+	isHub: boolean;
+}
+
 export interface GraphFilters {
 	mashTypes?: string[];
 	relationTypes?: string[];
 	sources?: string[];
+	clusters?: number[];
 }
 
 export function getGraph(filters: GraphFilters): Promise<GraphData> {
 	return invoke<GraphData>('get_graph', { filters });
 }
 
+/** The graph as it was at `timestamp` (Unix milliseconds). */
+export function getGraphAt(timestamp: number): Promise<GraphData> {
+	return invoke<GraphData>('get_graph_at', { timestamp });
+}
+
+export function diffGraph(from: number, to: number): Promise<GraphDiff> {
+	return invoke<GraphDiff>('diff_graph', { from, to });
+}
+
 export function getNodeDetail(id: string): Promise<GraphData> {
 	return invoke<GraphData>('get_node_detail', { id });
 }
@@ -73,3 +157,53 @@ export function updateNode(
 ): Promise<Mash> {
 	return invoke<Mash>('update_node', { id, mashType, summary, context, memo });
 }
+
+export function getRelationTypes(): Promise<RelationTypeDef[]> {
+	return invoke<RelationTypeDef[]>('get_relation_types');
+}
+
+export function saveRelationType(
+	code: string,
+	description: string,
+	directed: boolean,
+	inverseLabel?: string
+): Promise<RelationTypeDef> {
+	return invoke<RelationTypeDef>('save_relation_type', { code, description, directed, inverseLabel });
+}
+
+export function deleteRelationType(code: string): Promise<void> {
+	return invoke('delete_relation_type', { code });
+}
+
+export function getPendingEdges(limit?: number): Promise<EdgeReviewItem[]> {
+	return invoke<EdgeReviewItem[]>('get_pending_edges', { limit });
+}
+
+export function acceptEdge(id: number): Promise<void> {
+	return invoke('accept_edge', { id });
+}
+
+export function rejectEdge(id: number): Promise<void> {
+	return invoke('reject_edge', { id });
+}
+
+export function getGraphMetrics(): Promise<NodeMetrics[]> {
+	return invoke<NodeMetrics[]>('get_graph_metrics');
+}
+
+export function getClusters(): Promise<Cluster[]> {
+	return invoke<Cluster[]>('get_clusters');
+}
+
+export function refreshClusters(): Promise<Cluster[]> {
+	return invoke<Cluster[]>('refresh_clusters');
+}
+
+export function findPaths(
+	sourceId: string,
+	targetId: string,
+	k?: number,
+	skipConflicts?: boolean
+): Promise<PathResult> {
+	return invoke<PathResult>('find_paths', { sourceId, targetId, k, skipConflicts });
+}
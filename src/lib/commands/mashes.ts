@@ -1,5 +1,21 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { Mash, MashType } from '$lib/types';
+import type { DuplicateCandidate, Mash, MashRevision, MashType, MashTypeDef, MashTypeRole } from '$lib/types';
+import type { GraphEdge } from './graph';
+
+/** One atomic mash proposed by a split; edit freely before committing. */
+export interface SplitPart {
+	type: MashType;
+	summary: string;
+	context: string;
+	memo: string;
+	edgeIds: number[];
+}
+
+export interface SplitPreview {
+	original: Mash;
+	edges: GraphEdge[];
+	parts: SplitPart[];
+}
 
 export function getMashesByStatus(status: string, query?: string): Promise<Mash[]> {
 	return invoke<Mash[]>('get_mashes_by_status', { status, query });
@@ -31,6 +47,51 @@ export function searchMashes(query: string): Promise<Mash[]> {
 	return invoke<Mash[]>('search_keyword', { query });
 }
 
+export function findDuplicates(
+	minCosine?: number,
+	minTrigram?: number,
+	limit?: number
+): Promise<DuplicateCandidate[]> {
+	return invoke<DuplicateCandidate[]>('find_duplicates', { minCosine, minTrigram, limit });
+}
+
+export function dismissDuplicate(sourceId: string, targetId: string): Promise<void> {
+	return invoke('dismiss_duplicate', { sourceId, targetId });
+}
+
+export function mergeMashes(survivorId: string, mergedId: string): Promise<Mash> {
+	return invoke<Mash>('merge_mashes', { survivorId, mergedId });
+}
+
+export function getMashRevisions(id: string): Promise<MashRevision[]> {
+	return invoke<MashRevision[]>('get_mash_revisions', { id });
+}
+
+export function previewSplit(id: string): Promise<SplitPreview> {
+	return invoke<SplitPreview>('preview_split', { id });
+}
+
+export function splitMash(id: string, parts: SplitPart[]): Promise<Mash[]> {
+	return invoke<Mash[]>('split_mash', { id, parts });
+}
+
 export function addMashWithAI(text: string): Promise<Mash> {
 	return invoke<Mash>('add_mash_with_ai', { text });
 }
+
+export function getMashTypes(): Promise<MashTypeDef[]> {
+	return invoke<MashTypeDef[]>('get_mash_types');
+}
+
+export function saveMashType(
+	code: string,
+	labels: Record<string, string>,
+	description: string,
+	role: MashTypeRole | null
+): Promise<MashTypeDef> {
+	return invoke<MashTypeDef>('save_mash_type', { code, labels, description, role });
+}
+
+export function deleteMashType(code: string): Promise<void> {
+	return invoke('delete_mash_type', { code });
+}
@@ -7,6 +7,26 @@ export interface PipelineProgress {
 	total: number;
 }
 
+/** Spend today and this month (local time) against the `budget_*` settings. */
+export interface BudgetStatus {
+	dailyTokens: number;
+	dailyCost: number;
+	monthlyTokens: number;
+	monthlyCost: number;
+	dailyTokenCap: number | null;
+	dailyCostCap: number | null;
+	monthlyTokenCap: number | null;
+	monthlyCostCap: number | null;
+	/** Why capped phases are paused, or null while under every cap. */
+	exceeded: string | null;
+}
+
+/** Phases a run skipped because a spending cap was reached. */
+export interface PipelineRunResult {
+	pausedPhases: string[];
+	pausedReason: string | null;
+}
+
 export interface PipelineStatus {
 	lastRun: number | null;
 	nextRun: number | null;
@@ -16,10 +36,12 @@ export interface PipelineStatus {
 	jarredCount: number;
 	running: boolean;
 	progress: PipelineProgress | null;
+	budget: BudgetStatus;
+	lastRunPaused: PipelineRunResult;
 }
 
-export function triggerPipeline(): Promise<void> {
-	return invoke('trigger_pipeline');
+export function triggerPipeline(): Promise<PipelineRunResult> {
+	return invoke<PipelineRunResult>('trigger_pipeline');
 }
 
 export function setPipelineInterval(minutes: number): Promise<void> {
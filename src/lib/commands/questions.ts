@@ -0,0 +1,34 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export interface ResolutionCandidate {
+	edgeId: number;
+	mashId: string;
+	summary: string;
+	confidence: number;
+	rationale: string | null;
+}
+
+export interface OpenQuestion {
+	id: string;
+	type: string;
+	summary: string;
+	createdAt: number;
+	ageDays: number;
+	pagerank: number;
+	/** Age weighted by centrality; results are sorted by it, highest first. */
+	score: number;
+	/** Pending RESOLVED_BY proposals; accepting one resolves the question. */
+	candidates: ResolutionCandidate[];
+}
+
+export function getOpenQuestions(limit?: number): Promise<OpenQuestion[]> {
+	return invoke<OpenQuestion[]>('get_open_questions', { limit });
+}
+
+export function resolveQuestion(id: string, resolvedBy?: string): Promise<void> {
+	return invoke('resolve_question', { id, resolvedBy });
+}
+
+export function reopenQuestion(id: string): Promise<void> {
+	return invoke('reopen_question', { id });
+}
@@ -0,0 +1,37 @@
+import { invoke } from '@tauri-apps/api/core';
+
+/** 0 = forgotten … 5 = perfect recall; 3 and above count as remembered. */
+export type ReviewGrade = 0 | 1 | 2 | 3 | 4 | 5;
+
+export interface ReviewState {
+	mashId: string;
+	ease: number;
+	intervalDays: number;
+	repetitions: number;
+	dueAt: number;
+	lastReviewedAt: number | null;
+	lastGrade: number | null;
+}
+
+export interface DueReview {
+	id: string;
+	type: string;
+	summary: string;
+	context: string;
+	createdAt: number;
+	dueAt: number;
+	repetitions: number;
+	intervalDays: number;
+	pagerank: number;
+	inConflict: boolean;
+	score: number;
+}
+
+/** Omit `prioritize` to use the `review_prioritize` setting. */
+export function getDueReviews(limit?: number, prioritize?: boolean): Promise<DueReview[]> {
+	return invoke<DueReview[]>('get_due_reviews', { limit, prioritize });
+}
+
+export function recordReview(id: string, grade: ReviewGrade): Promise<ReviewState> {
+	return invoke<ReviewState>('record_review', { id, grade });
+}
@@ -1,4 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
+import type { EdgeReviewItem } from './graph';
 
 export function getSetting(key: string): Promise<string | null> {
 	return invoke<string | null>('get_setting', { key });
@@ -31,3 +32,48 @@ export function reextractRelationships(): Promise<number> {
 export function reembedAll(): Promise<number> {
 	return invoke<number>('reembed_all');
 }
+
+/** What a bulk operation would do, computed without calling a provider or writing. */
+export interface DryRunReport {
+	operation: string;
+	mashCount: number;
+	embeddingTexts: number;
+	cachedEmbeddings: number;
+	embeddingCalls: number;
+	reextractCount: number;
+	similarPairs: number;
+	chatBatches: number;
+	embeddingTokens: number;
+	chatPromptTokens: number;
+	chatCompletionTokens: number;
+	/** Null when a model involved has no price. */
+	estimatedCost: number | null;
+	edgesToDeleteCount: number;
+	/** The first pending AI edges that would be deleted. */
+	edgesToDelete: EdgeReviewItem[];
+}
+
+export function dryRunReextractRelationships(): Promise<DryRunReport> {
+	return invoke<DryRunReport>('dry_run_reextract_relationships');
+}
+
+export function dryRunReembedAll(): Promise<DryRunReport> {
+	return invoke<DryRunReport>('dry_run_reembed_all');
+}
+
+export interface EmbeddingCacheStats {
+	entries: number;
+	bytes: number;
+}
+
+export function getEmbeddingCacheStats(): Promise<EmbeddingCacheStats> {
+	return invoke<EmbeddingCacheStats>('get_embedding_cache_stats');
+}
+
+/** Omit `maxEntries` to trim to the `embedding_cache_max_entries` setting. */
+export function pruneEmbeddingCache(
+	olderThanDays?: number,
+	maxEntries?: number
+): Promise<EmbeddingCacheStats> {
+	return invoke<EmbeddingCacheStats>('prune_embedding_cache', { olderThanDays, maxEntries });
+}
@@ -0,0 +1,46 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export type TimelineBucket = 'day' | 'week';
+export type TimelineEventKind = 'captured' | 'distilled' | 'jarred' | 'edited' | 'linked';
+
+export interface ActivityBucket {
+	/** Local date the bucket starts on, `YYYY-MM-DD`; weeks start on Monday. */
+	start: string;
+	captured: Record<string, number>;
+	jarred: Record<string, number>;
+	linked: Record<string, number>;
+}
+
+export interface TimelineEvent {
+	kind: TimelineEventKind;
+	at: number;
+	mashId: string;
+	summary: string;
+	/** Mash type for mash events, relation type for links. */
+	category: string;
+	edgeId: number | null;
+	targetId: string | null;
+	targetSummary: string | null;
+}
+
+export interface TimelinePage {
+	events: TimelineEvent[];
+	total: number;
+}
+
+export function getActivityHistogram(
+	from: number,
+	to: number,
+	bucket?: TimelineBucket
+): Promise<ActivityBucket[]> {
+	return invoke<ActivityBucket[]>('get_activity_histogram', { from, to, bucket });
+}
+
+export function getTimelineFeed(
+	from: number,
+	to: number,
+	offset?: number,
+	limit?: number
+): Promise<TimelinePage> {
+	return invoke<TimelinePage>('get_timeline_feed', { from, to, offset, limit });
+}
@@ -0,0 +1,35 @@
+import { invoke } from '@tauri-apps/api/core';
+
+/** USD per million tokens, as stored in the `model_prices` setting. */
+export interface ModelPrice {
+	input: number;
+	output?: number;
+}
+
+export interface UsageReportRow {
+	/** Local date, `YYYY-MM-DD`. */
+	day: string;
+	provider: string;
+	model: string;
+	/** Pipeline phase or command that made the calls, e.g. `jar` or `ask`. */
+	phase: string;
+	calls: number;
+	promptTokens: number;
+	completionTokens: number;
+	/** Calls whose token counts were estimated from text length. */
+	estimatedCalls: number;
+	/** Estimated USD; null when the model has no price. */
+	cost: number | null;
+}
+
+export interface UsageReport {
+	rows: UsageReportRow[];
+	totalPromptTokens: number;
+	totalCompletionTokens: number;
+	totalCost: number;
+	unpricedModels: string[];
+}
+
+export function getUsageReport(from: number, to: number): Promise<UsageReport> {
+	return invoke<UsageReport>('get_usage_report', { from, to });
+}
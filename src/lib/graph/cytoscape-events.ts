@@ -89,7 +89,11 @@ export function setupDoubleClickEvents(cy: cytoscape.Core) {
 					targetId: e.targetId,
 					relationType: e.relationType as 'RELATED_TO' | 'SUPPORTS' | 'CONFLICTS_WITH',
 					source: e.source as 'ai' | 'human',
-					confidence: e.confidence
+					confidence: e.confidence,
+					rationale: e.rationale,
+					model: e.model,
+					extractedAt: e.extractedAt,
+					evidence: e.evidence
 				}))
 			});
 		} catch {
@@ -185,7 +189,11 @@ export async function handleContextMenuAction(
 							targetId: e.targetId,
 							relationType: e.relationType as 'RELATED_TO' | 'SUPPORTS' | 'CONFLICTS_WITH',
 							source: e.source as 'ai' | 'human',
-							confidence: e.confidence
+							confidence: e.confidence,
+							rationale: e.rationale,
+							model: e.model,
+							extractedAt: e.extractedAt,
+							evidence: e.evidence
 						}))
 					});
 				} catch {
@@ -201,9 +209,9 @@ export async function handleContextMenuAction(
 			break;
 		case 'delete-edge':
 			if (targetId && targetType === 'edge') {
-				const { sourceId, targetId: tId } = parseEdgeId(targetId);
+				const { sourceId, targetId: tId, relationType } = parseEdgeId(targetId);
 				uiStore.showConfirm(t('confirm.deleteEdge'), () => {
-					graphStore.removeEdge(sourceId, tId);
+					graphStore.removeEdge(sourceId, tId, relationType);
 					uiStore.dismissConfirm();
 				});
 			}
@@ -10,14 +10,14 @@ const en: Messages = {
 	'common.saved': 'Settings saved.',
 
 	// Mash types (display labels)
-	'type.결정': 'Decision',
-	'type.문제': 'Problem',
-	'type.인사이트': 'Insight',
-	'type.질문': 'Question',
-	'type.결정.desc': 'Confirmed decisions',
-	'type.문제.desc': 'Issues that need resolution',
-	'type.인사이트.desc': 'Discovered insights',
-	'type.질문.desc': 'Questions for discussion',
+	'type.decision': 'Decision',
+	'type.problem': 'Problem',
+	'type.insight': 'Insight',
+	'type.question': 'Question',
+	'type.decision.desc': 'Confirmed decisions',
+	'type.problem.desc': 'Issues that need resolution',
+	'type.insight.desc': 'Discovered insights',
+	'type.question.desc': 'Questions for discussion',
 
 	// Form
 	'form.summary': 'One-line summary *',
@@ -126,6 +126,7 @@ const en: Messages = {
 	'settings.pipelineRunning': 'Running...',
 	'settings.pipelineDone': 'Pipeline run completed.',
 	'settings.pipelineLastRun': 'Last run',
+	'settings.pipelinePaused': 'Extraction paused: {reason}',
 	'settings.pipelineNever': 'Never',
 	'settings.pipelineStats': 'Pipeline Stats',
 	'settings.onStill': 'On Still',
@@ -148,6 +149,8 @@ const en: Messages = {
 	'settings.progressReExtractSimilarity': 'Analyzing similarity',
 	'settings.progressReExtractApi': 'Extracting relationships',
 	'settings.progressReExtractSaving': 'Saving relationships',
+	'settings.progressClusterSummary': 'Summarizing topic clusters',
+	'settings.progressResolutions': 'Matching open questions to answers',
 	'settings.reembedBtn': 'Regenerate Embeddings',
 	'settings.reembedRunning': 'Regenerating embeddings...',
 	'settings.reembedDone': 'Embedding regeneration complete. {count} mashes reprocessed.',
@@ -158,6 +161,11 @@ const en: Messages = {
 	'settings.confirmReembedMsg1': 'All embeddings will be deleted and regenerated with the new model. This incurs API costs.',
 	'settings.confirmReembedMsg2': 'Search and graph features may not work correctly until reprocessing completes.',
 	'settings.confirmReembedMsg3': 'Graph relationships (edges) are preserved.',
+	'settings.dryRunTitle': 'Estimated impact',
+	'settings.dryRunEmbeddings': 'Embeddings: {count} to generate, {cached} from cache ({calls} API calls)',
+	'settings.dryRunExtraction': 'Re-extraction: {mashes} mashes, {pairs} similar pairs, {batches} chat calls',
+	'settings.dryRunCost': '~{tokens} tokens, about {cost}',
+	'settings.dryRunEdges': 'Unreviewed AI edges to delete: {count}',
 	'settings.confirmReembedProceed': 'Proceed',
 	'settings.confirmReextract': 'Confirm Re-extraction',
 	'settings.confirmReextractMsg1': 'All AI-generated relationships will be deleted and re-extracted. This incurs API costs.',
@@ -332,6 +340,10 @@ const en: Messages = {
 	'edge.to': 'To',
 	'edge.source': 'Source',
 	'edge.confidence': 'Confidence',
+	'edge.rationale': 'Why',
+	'edge.model': 'Model',
+	'edge.evidenceShared': 'Suggested by shared neighbors',
+	'edge.evidenceChain': 'Suggested by a SUPPORTS chain through',
 	'edge.edit': 'Edit',
 	'edge.delete': 'Delete',
 	'edge.relationType': 'Relation Type',
@@ -8,14 +8,14 @@ const ko = {
 	'common.saved': '저장되었습니다.',
 
 	// Mash types (display labels)
-	'type.결정': '결정',
-	'type.문제': '문제',
-	'type.인사이트': '인사이트',
-	'type.질문': '질문',
-	'type.결정.desc': '확정된 의사결정',
-	'type.문제.desc': '해결이 필요한 이슈',
-	'type.인사이트.desc': '발견한 통찰',
-	'type.질문.desc': '논의가 필요한 질문',
+	'type.decision': '결정',
+	'type.problem': '문제',
+	'type.insight': '인사이트',
+	'type.question': '질문',
+	'type.decision.desc': '확정된 의사결정',
+	'type.problem.desc': '해결이 필요한 이슈',
+	'type.insight.desc': '발견한 통찰',
+	'type.question.desc': '논의가 필요한 질문',
 
 	// Form
 	'form.summary': '한 줄 요약 *',
@@ -124,6 +124,7 @@ const ko = {
 	'settings.pipelineRunning': '실행 중...',
 	'settings.pipelineDone': '파이프라인 실행이 완료되었습니다.',
 	'settings.pipelineLastRun': '마지막 실행',
+	'settings.pipelinePaused': '관계 추출 일시 중지: {reason}',
 	'settings.pipelineNever': '없음',
 	'settings.pipelineStats': '파이프라인 통계',
 	'settings.onStill': '스틸 위',
@@ -146,6 +147,8 @@ const ko = {
 	'settings.progressReExtractSimilarity': '유사도 분석 중',
 	'settings.progressReExtractApi': '관계 추출 중',
 	'settings.progressReExtractSaving': '관계 저장 중',
+	'settings.progressClusterSummary': '주제 클러스터 요약 중',
+	'settings.progressResolutions': '열린 질문의 답 찾는 중',
 	'settings.reembedBtn': '임베딩 재생성',
 	'settings.reembedRunning': '임베딩 재생성 중...',
 	'settings.reembedDone': '임베딩 재생성이 완료되었습니다. {count}개 매시가 재처리되었습니다.',
@@ -156,6 +159,11 @@ const ko = {
 	'settings.confirmReembedMsg1': '모든 임베딩이 삭제되고 새 모델로 재생성됩니다. API 비용이 발생합니다.',
 	'settings.confirmReembedMsg2': '재처리가 완료될 때까지 검색 및 그래프 기능이 정상 동작하지 않을 수 있습니다.',
 	'settings.confirmReembedMsg3': '그래프 관계(엣지)는 보존됩니다.',
+	'settings.dryRunTitle': '예상 영향',
+	'settings.dryRunEmbeddings': '임베딩: 새로 생성 {count}개, 캐시 {cached}개 (API 호출 {calls}회)',
+	'settings.dryRunExtraction': '관계 재추출: 매시 {mashes}개, 유사 쌍 {pairs}개, 채팅 호출 {batches}회',
+	'settings.dryRunCost': '약 {tokens} 토큰, 예상 비용 {cost}',
+	'settings.dryRunEdges': '삭제될 미검토 AI 엣지: {count}개',
 	'settings.confirmReembedProceed': '변경',
 	'settings.confirmReextract': '관계 재추출 확인',
 	'settings.confirmReextractMsg1': 'AI가 생성한 모든 관계가 삭제되고 다시 추출됩니다. API 비용이 발생합니다.',
@@ -330,6 +338,10 @@ const ko = {
 	'edge.to': '도착',
 	'edge.source': '소스',
 	'edge.confidence': '신뢰도',
+	'edge.rationale': '근거',
+	'edge.model': '모델',
+	'edge.evidenceShared': '공통 이웃으로 제안됨',
+	'edge.evidenceChain': 'SUPPORTS 연쇄로 제안됨',
 	'edge.edit': '편집',
 	'edge.delete': '삭제',
 	'edge.relationType': '관계 유형',
@@ -40,7 +40,7 @@ function createGraphStore() {
 			})),
 		...filteredEdges.map((e) => ({
 			data: {
-				id: buildEdgeId(e.sourceId, e.targetId),
+				id: buildEdgeId(e.sourceId, e.targetId, e.relationType),
 				source: e.sourceId,
 				target: e.targetId,
 				relationType: e.relationType,
@@ -56,7 +56,7 @@ function createGraphStore() {
 
 	const selectedEdge = $derived(
 		selectedEdgeId
-			? (edges.find((e) => buildEdgeId(e.sourceId, e.targetId) === selectedEdgeId) ?? null)
+			? (edges.find((e) => buildEdgeId(e.sourceId, e.targetId, e.relationType) === selectedEdgeId) ?? null)
 			: null
 	);
 
@@ -80,13 +80,13 @@ function createGraphStore() {
 		mergeGraphData(data: { nodes: GraphNode[]; edges: GraphEdge[] }) {
 			const existingNodeIds = new Set(nodes.map((n) => n.id));
 			const existingEdgeIds = new Set(
-				edges.map((e) => buildEdgeId(e.sourceId, e.targetId))
+				edges.map((e) => buildEdgeId(e.sourceId, e.targetId, e.relationType))
 			);
 			nodes = [...nodes, ...data.nodes.filter((n) => !existingNodeIds.has(n.id))];
 			edges = [
 				...edges,
 				...data.edges.filter(
-					(e) => !existingEdgeIds.has(buildEdgeId(e.sourceId, e.targetId))
+					(e) => !existingEdgeIds.has(buildEdgeId(e.sourceId, e.targetId, e.relationType))
 				)
 			];
 		},
@@ -114,15 +114,20 @@ function createGraphStore() {
 		addEdge(edge: GraphEdge) {
 			edges = [...edges, edge];
 		},
-		updateEdge(sourceId: string, targetId: string, relationType: RelationType) {
+		updateEdge(sourceId: string, targetId: string, fromType: RelationType, relationType: RelationType) {
 			edges = edges.map((e) =>
-				e.sourceId === sourceId && e.targetId === targetId
+				e.sourceId === sourceId && e.targetId === targetId && e.relationType === fromType
 					? { ...e, relationType, source: 'human' as EdgeSource }
 					: e
 			);
+			if (selectedEdgeId === buildEdgeId(sourceId, targetId, fromType)) {
+				selectedEdgeId = buildEdgeId(sourceId, targetId, relationType);
+			}
 		},
-		removeEdge(sourceId: string, targetId: string) {
-			edges = edges.filter((e) => !(e.sourceId === sourceId && e.targetId === targetId));
+		removeEdge(sourceId: string, targetId: string, relationType: RelationType) {
+			edges = edges.filter(
+				(e) => !(e.sourceId === sourceId && e.targetId === targetId && e.relationType === relationType)
+			);
 		},
 		updateNode(id: string, updates: Partial<GraphNode>) {
 			nodes = nodes.map((n) => (n.id === id ? { ...n, ...updates } : n));
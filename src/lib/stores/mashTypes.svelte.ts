@@ -0,0 +1,24 @@
+import type { MashTypeDef } from '$lib/types';
+import * as cmd from '$lib/commands/mashes';
+import { showToast } from '$lib/stores/toast.svelte';
+import { getLocale } from '$lib/i18n/index.svelte';
+
+let mashTypes = $state<MashTypeDef[]>([]);
+
+export function getMashTypes() {
+	return mashTypes;
+}
+
+export async function loadMashTypes() {
+	try {
+		mashTypes = await cmd.getMashTypes();
+	} catch (e) {
+		showToast(String(e));
+	}
+}
+
+export function mashTypeLabel(code: string): string {
+	const def = mashTypes.find((mt) => mt.code === code);
+	if (!def) return code;
+	return def.labels[getLocale()] ?? def.labels.en ?? code;
+}
@@ -1,4 +1,21 @@
-export type MashType = '결정' | '문제' | '인사이트' | '질문';
+export type BuiltinMashType = 'decision' | 'problem' | 'insight' | 'question';
+
+/** Type code of a mash: a built-in code or one defined in the mash type registry. */
+export type MashType = BuiltinMashType | (string & {});
+
+/** What a type means to features that depend on it; see `MashTypeDef.role`. */
+export type MashTypeRole = 'open' | 'decision' | 'answer';
+
+export interface MashTypeDef {
+	code: string;
+	labels: Record<string, string>;
+	description: string;
+	position: number;
+	createdAt: number;
+	updatedAt: number;
+	/** `open` types can be resolved by `decision` and `answer` types. */
+	role: MashTypeRole | null;
+}
 
 export enum MashStatus {
 	MASH_TUN = 'MASH_TUN',
@@ -19,3 +36,26 @@ export interface Mash {
 	createdAt: number;
 	updatedAt: number;
 }
+
+export interface MashRevision {
+	id: number;
+	mashId: string;
+	originalId: string;
+	type: MashType;
+	summary: string;
+	context: string;
+	memo: string;
+	reason: string;
+	createdAt: number;
+}
+
+export interface DuplicateCandidate {
+	sourceId: string;
+	sourceSummary: string;
+	sourceStatus: MashStatus;
+	targetId: string;
+	targetSummary: string;
+	targetStatus: MashStatus;
+	cosine: number | null;
+	trigram: number;
+}
@@ -1,4 +1,7 @@
-export type RelationType = 'RELATED_TO' | 'SUPPORTS' | 'CONFLICTS_WITH';
+export type BuiltinRelationType = 'RELATED_TO' | 'SUPPORTS' | 'CONFLICTS_WITH';
+
+/** Relation code: a built-in type or one defined in the relation type registry. */
+export type RelationType = BuiltinRelationType | (string & {});
 export type EdgeSource = 'ai' | 'human';
 
 export interface GraphNode {
@@ -7,6 +10,7 @@ export interface GraphNode {
 	context: string;
 	memo: string;
 	type: string;
+	clusterId?: number | null;
 	createdAt?: number;
 	updatedAt?: number;
 }
@@ -17,6 +21,11 @@ export interface GraphEdge {
 	relationType: RelationType;
 	source: EdgeSource;
 	confidence: number | null;
+	rationale?: string | null;
+	model?: string | null;
+	extractedAt?: number | null;
+	/** JSON from link prediction, e.g. `{ method, score, sharedNeighbors, supportsChain }`. */
+	evidence?: string | null;
 	createdAt?: number;
 	updatedAt?: number;
 }
@@ -1,11 +1,12 @@
-export function buildEdgeId(sourceId: string, targetId: string): string {
-	return `${sourceId}__${targetId}`;
+export function buildEdgeId(sourceId: string, targetId: string, relationType: string): string {
+	return `${sourceId}__${targetId}__${relationType}`;
 }
 
-export function parseEdgeId(edgeId: string): { sourceId: string; targetId: string } {
-	const idx = edgeId.indexOf('__');
-	return {
-		sourceId: edgeId.slice(0, idx),
-		targetId: edgeId.slice(idx + 2)
-	};
+export function parseEdgeId(edgeId: string): {
+	sourceId: string;
+	targetId: string;
+	relationType: string;
+} {
+	const [sourceId, targetId, relationType] = edgeId.split('__');
+	return { sourceId, targetId, relationType };
 }